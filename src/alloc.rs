@@ -0,0 +1,170 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt,
+};
+
+/// Whether the binary was built with the counting allocator, see [`CountingAlloc`].
+pub const ENABLED: bool = cfg!(feature = "alloc-stats");
+
+/// Memory used by a piece of code, as seen by [`CountingAlloc`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Most bytes live at the same time, not counting memory allocated before.
+    pub peak: u64,
+    /// Bytes allocated in total, a growing reallocation counts its new size.
+    pub total: u64,
+    /// Number of allocations and reallocations.
+    pub count: u64,
+}
+
+#[derive(Clone, Copy)]
+struct Counters {
+    current: i64,
+    peak: i64,
+    total: u64,
+    count: u64,
+}
+
+thread_local! {
+    static COUNTERS: Cell<Counters> = const {
+        Cell::new(Counters { current: 0, peak: 0, total: 0, count: 0 })
+    };
+}
+
+fn record(grown: i64, allocated: usize) {
+    // fails while the thread is being torn down, nothing is measured then anyway
+    let _ = COUNTERS.try_with(|c| {
+        let mut counters = c.get();
+        counters.current += grown;
+        counters.peak = counters.peak.max(counters.current);
+        counters.total += allocated as u64;
+        counters.count += u64::from(allocated > 0);
+        c.set(counters);
+    });
+}
+
+/// Global allocator forwarding to [`System`] while counting allocations per thread.
+///
+/// Installed by the binary when built with the `alloc-stats` feature, [`measure`] reads the
+/// counters.
+pub struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size() as i64, layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size() as i64, layout.size());
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(-(layout.size() as i64), 0);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size as i64 - layout.size() as i64, new_size);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Run `f` and report what it allocated on the current thread, `None` without the counting
+/// allocator.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    if !ENABLED {
+        return (f(), None);
+    }
+    let reset = Counters {
+        current: 0,
+        peak: 0,
+        total: 0,
+        count: 0,
+    };
+    let before = COUNTERS.with(|c| c.replace(reset));
+    let result = f();
+    let after = COUNTERS.with(|c| c.get());
+    // keep counting for an enclosing measurement
+    COUNTERS.with(|c| {
+        c.set(Counters {
+            current: before.current + after.current,
+            peak: before.peak.max(before.current + after.peak),
+            total: before.total + after.total,
+            count: before.count + after.count,
+        })
+    });
+    let stats = AllocStats {
+        peak: after.peak.max(0) as u64,
+        total: after.total,
+        count: after.count,
+    };
+    (result, Some(stats))
+}
+
+/// Human friendly byte count with binary units.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.2}{}", UNITS[unit])
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "peak {}, total {} in {} allocations",
+            format_bytes(self.peak),
+            format_bytes(self.total),
+            self.count
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "alloc-stats")]
+    #[global_allocator]
+    static ALLOC: CountingAlloc = CountingAlloc;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(12), "12B");
+        assert_eq!(format_bytes(1536), "1.50KiB");
+        assert_eq!(format_bytes(3 << 20), "3.00MiB");
+    }
+
+    #[cfg(feature = "alloc-stats")]
+    #[test]
+    fn test_measure() {
+        let (_, stats) = measure(|| {
+            let a = vec![0u8; 1000];
+            drop(a);
+            let mut b: Vec<u8> = Vec::with_capacity(600);
+            b.resize(600, 1);
+            b
+        });
+        let stats = stats.unwrap();
+        assert_eq!(stats.peak, 1000);
+        assert_eq!(stats.total, 1600);
+        assert_eq!(stats.count, 2);
+    }
+
+    #[cfg(not(feature = "alloc-stats"))]
+    #[test]
+    fn test_measure_disabled() {
+        assert_eq!(measure(|| vec![0u8; 1000].len()), (1000, None));
+    }
+}
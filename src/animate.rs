@@ -0,0 +1,643 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    geometry::Point,
+    grid::Grid,
+    render::{self, Render, Rgb},
+};
+
+/// Gets to look at a simulation after each of its steps.
+///
+/// Any `FnMut(&S)` is an observer, e.g. `|rope: &Rope| println!("{rope}")`, and `()` is the
+/// one doing nothing, which the plain simulations use.
+pub trait Observer<S: ?Sized> {
+    fn observe(&mut self, state: &S);
+}
+
+impl<S: ?Sized, F: FnMut(&S)> Observer<S> for F {
+    fn observe(&mut self, state: &S) {
+        self(state)
+    }
+}
+
+impl<S: ?Sized> Observer<S> for () {
+    fn observe(&mut self, _: &S) {}
+}
+
+/// A captured picture, cut down to the cells that are not background.
+#[derive(Debug, Clone)]
+struct Frame {
+    /// Where the top left corner of `image` lies in the world.
+    origin: Point<isize>,
+    image: Grid<Rgb>,
+}
+
+/// Bounds of a set of world cells, the right and bottom edges excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Region {
+    left: isize,
+    top: isize,
+    right: isize,
+    bottom: isize,
+}
+
+impl Region {
+    fn of(frame: &Frame) -> Option<Self> {
+        (frame.image.width() > 0 && frame.image.height() > 0).then(|| Self {
+            left: frame.origin.x,
+            top: frame.origin.y,
+            right: frame.origin.x + frame.image.width() as isize,
+            bottom: frame.origin.y + frame.image.height() as isize,
+        })
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    fn expand(self, margin: usize) -> Self {
+        let margin = margin as isize;
+        Self {
+            left: self.left - margin,
+            top: self.top - margin,
+            right: self.right + margin,
+            bottom: self.bottom + margin,
+        }
+    }
+}
+
+/// Observer keeping a picture of the states it sees, to be saved as an animation.
+///
+/// Only one state out of every [`Recorder::with_frame_skip`] is drawn. The saved frames are
+/// cut down to the active region, the box around every cell that was ever not background,
+/// plus a margin.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    every: usize,
+    margin: usize,
+    seen: usize,
+    background: Rgb,
+    frames: Vec<Frame>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            every: 1,
+            margin: 1,
+            seen: 0,
+            background: Rgb::BLACK,
+            frames: vec![],
+        }
+    }
+}
+
+impl Recorder {
+    /// Keep the first state and then one out of every `every`.
+    pub fn with_frame_skip(mut self, every: usize) -> Self {
+        assert!(every > 0, "cannot keep one frame out of zero");
+        self.every = every;
+        self
+    }
+
+    /// Cells of background kept around the active region.
+    pub fn with_margin(mut self, margin: usize) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Draw `state` whether or not it would be skipped, e.g. to end on the final state.
+    pub fn capture(&mut self, state: &impl Render) {
+        let image = state.render();
+        let background = state.background();
+        self.background = background;
+
+        let mut bounds: Option<(Point<usize>, Point<usize>)> = None;
+        for (pos, _) in image.iter().filter(|(_, c)| **c != background) {
+            bounds = Some(match bounds {
+                None => (pos, pos),
+                Some((low, high)) => (
+                    Point::new(low.x.min(pos.x), low.y.min(pos.y)),
+                    Point::new(high.x.max(pos.x), high.y.max(pos.y)),
+                ),
+            });
+        }
+        let origin = state.origin();
+        let frame = match bounds {
+            None => Frame {
+                origin,
+                image: Grid::default(),
+            },
+            Some((low, high)) => {
+                let width = high.x - low.x + 1;
+                let cells = (low.y..=high.y)
+                    .flat_map(|y| &image.row(y)[low.x..=high.x])
+                    .copied()
+                    .collect();
+                Frame {
+                    origin: Point::new(origin.x + low.x as isize, origin.y + low.y as isize),
+                    image: Grid::from_cells(width, cells),
+                }
+            }
+        };
+        self.frames.push(frame);
+    }
+
+    /// Number of states observed so far, drawn or skipped.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Number of frames captured so far.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The captured frames, all cropped to the active region.
+    pub fn frames(&self) -> Vec<Grid<Rgb>> {
+        if self.frames.is_empty() {
+            return vec![];
+        }
+        let region = self
+            .frames
+            .iter()
+            .filter_map(Region::of)
+            .reduce(Region::union)
+            .unwrap_or(Region {
+                left: 0,
+                top: 0,
+                right: 1,
+                bottom: 1,
+            })
+            .expand(self.margin);
+        let width = (region.right - region.left) as usize;
+        let height = (region.bottom - region.top) as usize;
+        self.frames
+            .iter()
+            .map(|frame| {
+                let mut canvas = Grid::new(width, height, self.background);
+                let dx = (frame.origin.x - region.left) as usize;
+                let dy = (frame.origin.y - region.top) as usize;
+                for (pos, &colour) in frame.image.iter() {
+                    canvas[Point::new(pos.x + dx, pos.y + dy)] = colour;
+                }
+                canvas
+            })
+            .collect()
+    }
+
+    /// The frames with cells of `factor` by `factor` pixels, refusing empty pictures.
+    fn scaled_frames(&self, factor: usize) -> io::Result<Vec<Grid<Rgb>>> {
+        let invalid = |message| Err(io::Error::new(ErrorKind::InvalidInput, message));
+        if self.frames.is_empty() {
+            return invalid("no frame was captured");
+        }
+        if factor == 0 {
+            return invalid("frames scaled by 0 would be empty pictures");
+        }
+        Ok(self
+            .frames()
+            .iter()
+            .map(|f| render::scale(f, factor))
+            .collect())
+    }
+
+    /// Write the frames as a looping GIF with cells of `factor` by `factor` pixels, showing
+    /// each frame for `delay`.
+    pub fn save_gif(&self, path: &Path, factor: usize, delay: Duration) -> io::Result<()> {
+        let frames = self.scaled_frames(factor)?;
+        if frames[0].width() > MAX_GIF_SIDE || frames[0].height() > MAX_GIF_SIDE {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "frames of {}x{} pixels are too large for a GIF",
+                    frames[0].width(),
+                    frames[0].height()
+                ),
+            ));
+        }
+        fs::write(path, gif(&frames, delay))
+    }
+
+    /// Write the frames as `frame_00000.ppm`, `frame_00001.ppm`, ... in `dir`, creating it if
+    /// needed, with cells of `factor` by `factor` pixels.
+    pub fn save_ppm_sequence(&self, dir: &Path, factor: usize) -> io::Result<()> {
+        let frames = self.scaled_frames(factor)?;
+        fs::create_dir_all(dir)?;
+        for (i, frame) in frames.iter().enumerate() {
+            let path = dir.join(format!("frame_{i:05}.ppm"));
+            fs::write(path, render::ppm(frame))?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: Render> Observer<S> for Recorder {
+    fn observe(&mut self, state: &S) {
+        if self.seen.is_multiple_of(self.every) {
+            self.capture(state);
+        }
+        self.seen += 1;
+    }
+}
+
+/// Widest and highest picture a GIF holds.
+const MAX_GIF_SIDE: usize = u16::MAX as usize;
+
+/// Looping animated GIF of `frames`, each shown for `delay`, rounded to hundredths of a second.
+///
+/// Pictures with more than 256 colours are reduced to a 6x6x6 colour cube.
+///
+/// Panics if there is no frame, if the frames differ in size or are too large for a GIF.
+pub fn gif(frames: &[Grid<Rgb>], delay: Duration) -> Vec<u8> {
+    assert!(!frames.is_empty(), "a GIF needs at least one frame");
+    let (width, height) = (frames[0].width(), frames[0].height());
+    assert!(
+        frames
+            .iter()
+            .all(|f| (f.width(), f.height()) == (width, height)),
+        "frames of a GIF have the same size"
+    );
+    let width = u16::try_from(width).expect("GIF too wide");
+    let height = u16::try_from(height).expect("GIF too high");
+    let delay = u16::try_from(delay.as_millis() / 10).unwrap_or(u16::MAX);
+
+    let (colours, index) = palette(frames);
+    // the colour table holds a power of two colours, at least two
+    let bits = (usize::BITS - (colours.len() - 1).max(1).leading_zeros()) as u8;
+
+    let mut bytes = b"GIF89a".to_vec();
+    bytes.extend(width.to_le_bytes());
+    bytes.extend(height.to_le_bytes());
+    // global colour table, its colour resolution and size, then background and aspect ratio
+    bytes.extend([0x80 | (bits - 1) << 4 | (bits - 1), 0, 0]);
+    for i in 0..1 << bits {
+        let Rgb(r, g, b) = colours.get(i).copied().unwrap_or_default();
+        bytes.extend([r, g, b]);
+    }
+    // application extension looping forever
+    bytes.extend([0x21, 0xff, 11]);
+    bytes.extend(b"NETSCAPE2.0");
+    bytes.extend([3, 1, 0, 0, 0]);
+
+    for frame in frames {
+        // graphic control extension with the delay
+        bytes.extend([0x21, 0xf9, 4, 0]);
+        bytes.extend(delay.to_le_bytes());
+        bytes.extend([0, 0]);
+        // image descriptor covering the whole picture, without a local colour table
+        bytes.push(0x2c);
+        bytes.extend([0, 0, 0, 0]);
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes.push(0);
+
+        let min_code_size = bits.max(2);
+        let indices: Vec<u8> = frame.iter().map(|(_, c)| index[c]).collect();
+        bytes.push(min_code_size);
+        for block in lzw(min_code_size, &indices).chunks(255) {
+            bytes.push(block.len() as u8);
+            bytes.extend(block);
+        }
+        bytes.push(0);
+    }
+    bytes.push(0x3b);
+    bytes
+}
+
+/// Colours of the frames in order of appearance and the index of each, or a colour cube if
+/// they do not fit in a GIF colour table.
+fn palette(frames: &[Grid<Rgb>]) -> (Vec<Rgb>, HashMap<Rgb, u8>) {
+    let mut colours = vec![];
+    let mut index = HashMap::new();
+    for (_, &colour) in frames.iter().flat_map(Grid::iter) {
+        index.entry(colour).or_insert_with(|| {
+            colours.push(colour);
+            (colours.len() - 1) as u8
+        });
+    }
+    if colours.len() <= 256 {
+        return (colours, index);
+    }
+
+    let level = |v: u8| (u16::from(v) * 5 + 127) / 255;
+    for (colour, i) in &mut index {
+        *i = (level(colour.0) * 36 + level(colour.1) * 6 + level(colour.2)) as u8;
+    }
+    let cube = (0..216u16)
+        .map(|i| {
+            Rgb(
+                (i / 36 * 51) as u8,
+                (i / 6 % 6 * 51) as u8,
+                (i % 6 * 51) as u8,
+            )
+        })
+        .collect();
+    (cube, index)
+}
+
+/// Largest code of GIF's LZW.
+const MAX_CODE: u16 = 4095;
+
+/// Variable width LZW compression of colour `indices`, as GIF image data.
+fn lzw(min_code_size: u8, indices: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut bits = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut width = min_code_size + 1;
+    let mut next = end + 1;
+
+    bits.write(clear, width);
+    let mut rest = indices.iter();
+    if let Some(&first) = rest.next() {
+        let mut prefix = u16::from(first);
+        for &index in rest {
+            if let Some(&code) = table.get(&(prefix, index)) {
+                prefix = code;
+                continue;
+            }
+            bits.write(prefix, width);
+            table.insert((prefix, index), next);
+            prefix = u16::from(index);
+            next += 1;
+            // the decoder learns each code one step late, so widens one code later too
+            if next > 1 << width && width < 12 {
+                width += 1;
+            }
+            if next > MAX_CODE {
+                bits.write(clear, width);
+                table.clear();
+                width = min_code_size + 1;
+                next = end + 1;
+            }
+        }
+        bits.write(prefix, width);
+    }
+    bits.write(end, width);
+    bits.finish()
+}
+
+/// Packs codes least significant bit first, as GIF wants them.
+#[derive(Debug, Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.pending |= u32::from(code) << self.len;
+        self.len += width;
+        while self.len >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Colour indices out of GIF image data, the reverse of [`lzw`].
+    fn unlzw(min_code_size: u8, bytes: &[u8]) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let mut table: Vec<Vec<u8>> = (0..clear).map(|i| vec![i as u8]).collect();
+        table.extend([vec![], vec![]]);
+        let mut width = min_code_size + 1;
+        let (mut pending, mut len, mut at) = (0u32, 0, 0);
+        let mut previous: Option<Vec<u8>> = None;
+        let mut indices = vec![];
+        loop {
+            while len < width {
+                pending |= u32::from(bytes[at]) << len;
+                at += 1;
+                len += 8;
+            }
+            let code = (pending & ((1 << width) - 1)) as usize;
+            pending >>= width;
+            len -= width;
+            if code == clear {
+                table.truncate(clear + 2);
+                width = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                break;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(p)) => [p.as_slice(), &p[..1]].concat(),
+                (None, None) => panic!("unknown code {code}"),
+            };
+            if let Some(p) = previous {
+                table.push([p.as_slice(), &entry[..1]].concat());
+            }
+            if table.len() == 1 << width && width < 12 {
+                width += 1;
+            }
+            indices.extend(&entry);
+            previous = Some(entry);
+        }
+        assert_eq!(at, bytes.len(), "data after the end code");
+        indices
+    }
+
+    /// Frames of image data out of a GIF, checking the blocks around them.
+    fn gif_frames(bytes: &[u8]) -> Vec<Vec<u8>> {
+        assert_eq!(bytes[..6], *b"GIF89a");
+        let bits = (bytes[10] & 7) + 1;
+        let mut at = 13 + 3 * (1 << bits);
+        let mut frames = vec![];
+        loop {
+            match bytes[at] {
+                0x21 => {
+                    at += 2;
+                    while bytes[at] != 0 {
+                        at += bytes[at] as usize + 1;
+                    }
+                    at += 1;
+                }
+                0x2c => {
+                    let min_code_size = bytes[at + 10];
+                    at += 11;
+                    let mut data = vec![];
+                    while bytes[at] != 0 {
+                        let len = bytes[at] as usize;
+                        data.extend(&bytes[at + 1..at + 1 + len]);
+                        at += len + 1;
+                    }
+                    at += 1;
+                    frames.push(unlzw(min_code_size, &data));
+                }
+                0x3b => break,
+                other => panic!("unexpected block {other:#x}"),
+            }
+        }
+        assert_eq!(at + 1, bytes.len());
+        frames
+    }
+
+    #[test]
+    fn test_lzw() {
+        // the 10 by 10 sample of "What's in a GIF", four colours
+        let rows = [
+            "1111122222",
+            "1111122222",
+            "1111122222",
+            "1110000222",
+            "1110000222",
+            "2220000111",
+            "2220000111",
+            "2222211111",
+            "2222211111",
+            "2222211111",
+        ];
+        let indices: Vec<u8> = rows.concat().bytes().map(|b| b - b'0').collect();
+        let expected = [
+            0x8c, 0x2d, 0x99, 0x87, 0x2a, 0x1c, 0xdc, 0x33, 0xa0, 0x02, 0x75, 0xec, 0x95, 0xfa,
+            0xa8, 0xde, 0x60, 0x8c, 0x04, 0x91, 0x4c, 0x01,
+        ];
+        assert_eq!(lzw(2, &indices), expected);
+        assert_eq!(unlzw(2, &expected), indices);
+        assert_eq!(unlzw(2, &lzw(2, &[])), []);
+
+        // enough varied data to fill the code table several times
+        let mut x = 1u32;
+        let noisy: Vec<u8> = (0..50_000)
+            .map(|_| {
+                x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (x >> 16) as u8 % 7
+            })
+            .collect();
+        assert_eq!(unlzw(3, &lzw(3, &noisy)), noisy);
+        let wide: Vec<u8> = (0..20_000u32).map(|i| (i * 7 % 256) as u8).collect();
+        assert_eq!(unlzw(8, &lzw(8, &wide)), wide);
+    }
+
+    #[test]
+    fn test_gif() {
+        let red = Rgb(255, 0, 0);
+        let one = Grid::from_cells(2, vec![Rgb::BLACK, red, red, Rgb::WHITE]);
+        let two = Grid::new(2, 2, Rgb::WHITE);
+        let bytes = gif(&[one, two], Duration::from_millis(50));
+        // 2 by 2, three colours in a table of four
+        assert_eq!(bytes[6..11], [2, 0, 2, 0, 0x80 | 1 << 4 | 1]);
+        assert_eq!(bytes[13..25], [0, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0, 0]);
+        let control = bytes.windows(2).position(|w| w == [0x21, 0xf9]).unwrap();
+        assert_eq!(bytes[control + 4..control + 6], 5u16.to_le_bytes());
+        assert_eq!(gif_frames(&bytes), [vec![0, 1, 1, 2], vec![2, 2, 2, 2]]);
+
+        // too many colours for a table
+        let shades = (0..300)
+            .map(|i| Rgb((i % 256) as u8, (i / 256) as u8, 0))
+            .collect();
+        let shades = Grid::from_cells(300, shades);
+        let (colours, index) = palette(&[shades]);
+        assert_eq!(colours.len(), 216);
+        assert_eq!(colours[index[&Rgb(255, 0, 0)] as usize], Rgb(255, 0, 0));
+        assert_eq!(colours[index[&Rgb(50, 0, 0)] as usize], Rgb(51, 0, 0));
+    }
+
+    /// A dot walking right on a line that grows with it.
+    struct Walk(usize);
+
+    impl Render for Walk {
+        fn size(&self) -> (usize, usize) {
+            (self.0 + 3, 3)
+        }
+
+        fn colour(&self, pos: Point<usize>) -> Rgb {
+            if pos == Point::new(self.0 + 1, 1) {
+                Rgb::WHITE
+            } else {
+                Rgb(1, 1, 1)
+            }
+        }
+
+        fn origin(&self) -> Point<isize> {
+            Point::new(-1, -1)
+        }
+
+        fn background(&self) -> Rgb {
+            Rgb(1, 1, 1)
+        }
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut recorder = Recorder::default().with_frame_skip(3).with_margin(0);
+        for step in 0..8 {
+            recorder.observe(&Walk(step));
+        }
+        recorder.capture(&Walk(10));
+        // steps 0, 3, 6 and the last
+        assert_eq!(recorder.len(), 4);
+        assert_eq!(recorder.seen(), 8);
+        let frames = recorder.frames();
+        // the dot went from x = 0 to 10, the rest is background
+        assert!(frames.iter().all(|f| (f.width(), f.height()) == (11, 1)));
+        let dots: Vec<usize> = frames
+            .iter()
+            .map(|f| f.row(0).iter().position(|&c| c == Rgb::WHITE).unwrap())
+            .collect();
+        assert_eq!(dots, [0, 3, 6, 10]);
+
+        let mut recorder = Recorder::default();
+        recorder.observe(&Walk(2));
+        let frames = recorder.frames();
+        assert_eq!((frames[0].width(), frames[0].height()), (3, 3));
+        assert_eq!(frames[0][Point::new(1, 1)], Rgb::WHITE);
+
+        let dir = std::env::temp_dir().join(format!("aoc-animate-{}", std::process::id()));
+        recorder.save_ppm_sequence(&dir, 2).unwrap();
+        let first = fs::read(dir.join("frame_00000.ppm")).unwrap();
+        assert!(first.starts_with(b"P6\n6 6\n255\n"));
+        recorder
+            .save_gif(&dir.join("walk.gif"), 1, Duration::ZERO)
+            .unwrap();
+        assert!(
+            fs::read(dir.join("walk.gif"))
+                .unwrap()
+                .starts_with(b"GIF89a")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+        let error = Recorder::default()
+            .save_gif(&dir.join("none.gif"), 1, Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let error = recorder
+            .save_gif(&dir.join("empty.gif"), 0, Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let error = recorder.save_ppm_sequence(&dir, 0).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    cell::Cell,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{Part, Puzzle, runner::format_duration};
+
+thread_local! {
+    static PARSE_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Mark `f` as the input parsing step of a solver.
+///
+/// Benchmarks report the time spent in here apart from the rest of the solution, outside of a
+/// benchmark it only costs a clock read.
+pub fn parsing<T>(f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    PARSE_TIME.with(|t| t.set(t.get() + start.elapsed()));
+    result
+}
+
+fn take_parse_time() -> Duration {
+    PARSE_TIME.with(|t| t.replace(Duration::ZERO))
+}
+
+/// Summary of a set of timings.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        };
+        let mean = sorted.iter().map(|d| d.as_secs_f64()).sum::<f64>() / n as f64;
+        let variance = sorted
+            .iter()
+            .map(|d| (d.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / n as f64;
+        Self {
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// Timings of repeated runs of one part.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub day: u32,
+    pub part: Part,
+    pub warmup: usize,
+    pub iterations: usize,
+    pub parse: Stats,
+    pub solve: Stats,
+    pub total: Stats,
+}
+
+/// Run one part `warmup` times untimed, then `iterations` times timed.
+pub fn bench(
+    puzzle: &dyn Puzzle,
+    part: Part,
+    input: &str,
+    warmup: usize,
+    iterations: usize,
+) -> Report {
+    for _ in 0..warmup {
+        puzzle.run(part, input);
+    }
+    let mut parse = Vec::with_capacity(iterations);
+    let mut solve = Vec::with_capacity(iterations);
+    let mut total = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        take_parse_time();
+        let start = Instant::now();
+        std::hint::black_box(puzzle.run(part, input));
+        let elapsed = start.elapsed();
+        let parse_time = take_parse_time().min(elapsed);
+        parse.push(parse_time);
+        solve.push(elapsed - parse_time);
+        total.push(elapsed);
+    }
+    Report {
+        day: puzzle.day(),
+        part,
+        warmup,
+        iterations,
+        parse: Stats::from_samples(&parse),
+        solve: Stats::from_samples(&solve),
+        total: Stats::from_samples(&total),
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "day {:02} {}: {} iterations after {} warmup runs",
+            self.day, self.part, self.iterations, self.warmup
+        )?;
+        writeln!(
+            f,
+            "{:<6} {:>10} {:>10} {:>10} {:>10}",
+            "", "min", "median", "mean", "stddev"
+        )?;
+        for (name, stats) in [
+            ("parse", &self.parse),
+            ("solve", &self.solve),
+            ("total", &self.total),
+        ] {
+            writeln!(
+                f,
+                "{name:<6} {:>10} {:>10} {:>10} {:>10}",
+                format_duration(stats.min),
+                format_duration(stats.median),
+                format_duration(stats.mean),
+                format_duration(stats.stddev)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Answer, Solver};
+
+    #[test]
+    fn test_stats() {
+        let samples: Vec<Duration> = [4, 2, 8, 6]
+            .into_iter()
+            .map(Duration::from_millis)
+            .collect();
+        let stats = Stats::from_samples(&samples);
+        assert_eq!(stats.min, Duration::from_millis(2));
+        assert_eq!(stats.median, Duration::from_millis(5));
+        assert_eq!(stats.mean, Duration::from_millis(5));
+        assert_eq!(stats.stddev.as_micros(), 2236);
+    }
+
+    #[test]
+    fn test_parsing_is_recorded() {
+        take_parse_time();
+        parsing(|| std::thread::sleep(Duration::from_millis(1)));
+        assert!(take_parse_time() >= Duration::from_millis(1));
+        assert_eq!(take_parse_time(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_normalizing_is_parsing() {
+        struct Noop;
+        impl Solver for Noop {
+            const DAY: u32 = 1;
+
+            fn part1(&self, _input: &str) -> Answer {
+                Answer::Integer(0)
+            }
+
+            fn part2(&self, _input: &str) -> Answer {
+                Answer::Integer(0)
+            }
+        }
+        take_parse_time();
+        Noop.run_part1(&"1 \r\n".repeat(100_000));
+        assert!(take_parse_time() > Duration::ZERO);
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::input::INPUT_DIR;
+
+/// Year of the puzzles solved in this crate.
+pub const YEAR: u32 = 2022;
+
+/// Site the puzzles are served from.
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Overrides [`DEFAULT_BASE_URL`], e.g. to point at a local stub server.
+pub const BASE_URL_ENV: &str = "AOC_BASE_URL";
+
+/// Holds the value of the `session` cookie of a logged in browser.
+pub const SESSION_ENV: &str = "AOC_SESSION";
+
+/// Path of a file holding the session cookie, checked when [`SESSION_ENV`] is not set.
+pub const SESSION_FILE_ENV: &str = "AOC_SESSION_FILE";
+
+/// Session file used when neither variable is set, kept out of git.
+pub const DEFAULT_SESSION_FILE: &str = ".session";
+
+/// Shortest time between two requests, also across separate runs of the binary.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(3);
+
+/// File remembering when the last request was sent.
+const LAST_REQUEST_FILE: &str = ".last_request";
+
+const USER_AGENT: &str = concat!(
+    "aoc_2022/",
+    env!("CARGO_PKG_VERSION"),
+    " (personal puzzle runner)"
+);
+
+/// Read the session cookie from [`SESSION_ENV`], or else from the file named by
+/// [`SESSION_FILE_ENV`] or [`DEFAULT_SESSION_FILE`].
+pub fn session_from_env() -> Result<String> {
+    if let Ok(session) = env::var(SESSION_ENV) {
+        return Ok(session.trim().to_string());
+    }
+    let path = env::var(SESSION_FILE_ENV).unwrap_or_else(|_| DEFAULT_SESSION_FILE.to_string());
+    let session = fs::read_to_string(&path).with_context(|| {
+        format!("no session token, set {SESSION_ENV} or put it in {path} ({SESSION_FILE_ENV})")
+    })?;
+    let session = session.trim();
+    if session.is_empty() {
+        bail!("session token file {path} is empty");
+    }
+    Ok(session.to_string())
+}
+
+/// Logged in access to the puzzle site.
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    /// Where the time of the last request is kept between runs.
+    throttle: PathBuf,
+    min_interval: Duration,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str, root: &Path) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(30)))
+            .user_agent(USER_AGENT)
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Self {
+            agent,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            throttle: root.join(INPUT_DIR).join(LAST_REQUEST_FILE),
+            min_interval: MIN_INTERVAL,
+        }
+    }
+
+    /// Client for the crate at `root`, configured from the environment.
+    pub fn from_env(root: &Path) -> Result<Self> {
+        let base_url = env::var(BASE_URL_ENV).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Ok(Self::new(&base_url, &session_from_env()?, root))
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// URL of `path` below the pages of `day`.
+    fn day_url(&self, day: u32, path: &str) -> String {
+        format!("{}/{YEAR}/day/{day}{path}", self.base_url)
+    }
+
+    /// Sleep until [`MIN_INTERVAL`] has passed since the last request, then note this one.
+    fn wait_turn(&self) -> Result<()> {
+        let last = fs::read_to_string(&self.throttle)
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map(|ms| UNIX_EPOCH + Duration::from_millis(ms));
+        if let Some(since) = last.and_then(|t| SystemTime::now().duration_since(t).ok()) {
+            if since < self.min_interval {
+                thread::sleep(self.min_interval - since);
+            }
+        }
+        if let Some(dir) = self.throttle.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        fs::write(&self.throttle, now.to_string())
+            .with_context(|| format!("failed to write {}", self.throttle.display()))
+    }
+
+    fn read(
+        url: &str,
+        response: Result<ureq::http::Response<ureq::Body>, ureq::Error>,
+    ) -> Result<(u16, String)> {
+        let mut response = response.with_context(|| format!("request to {url} failed"))?;
+        let status = response.status().as_u16();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .with_context(|| format!("failed to read response of {url}"))?;
+        Ok((status, body))
+    }
+
+    /// GET `path` of `day`, returning the status code and body.
+    pub fn get(&self, day: u32, path: &str) -> Result<(u16, String)> {
+        self.wait_turn()?;
+        let url = self.day_url(day, path);
+        let response = self
+            .agent
+            .get(&url)
+            .header("Cookie", &format!("session={}", self.session))
+            .call();
+        Self::read(&url, response)
+    }
+
+    /// POST a form to `path` of `day`, returning the status code and body.
+    pub fn post_form(&self, day: u32, path: &str, form: &[(&str, &str)]) -> Result<(u16, String)> {
+        self.wait_turn()?;
+        let url = self.day_url(day, path);
+        let response = self
+            .agent
+            .post(&url)
+            .header("Cookie", &format!("session={}", self.session))
+            .send_form(form.iter().copied());
+        Self::read(&url, response)
+    }
+}
+
+/// A minimal HTTP server answering with canned responses, standing in for the puzzle site.
+#[cfg(test)]
+pub(crate) mod stub {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    /// Serve `responses` in order, one per connection. Returns the base URL and a handle
+    /// yielding every request received, head and body.
+    pub fn serve(responses: Vec<(u16, &'static str)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = vec![];
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                }
+                let mut content = vec![0; length];
+                reader.read_exact(&mut content).unwrap();
+                request.push_str(&String::from_utf8(content).unwrap());
+                requests.push(request);
+                let response = format!(
+                    "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    /// Scratch directory for a test, emptied first.
+    pub fn temp_root(name: &str) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("aoc_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let (url, server) = stub::serve(vec![(200, "hello")]);
+        let root = stub::temp_root("client_get");
+        let client = Client::new(&url, "abc", &root);
+        assert_eq!(client.get(3, "/input").unwrap(), (200, "hello".to_string()));
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /2022/day/3/input HTTP/1.1\r\n"));
+        assert!(requests[0].contains("session=abc"));
+        assert!(root.join("input").join(LAST_REQUEST_FILE).exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_throttle() {
+        let (url, server) = stub::serve(vec![(200, ""), (404, "")]);
+        let root = stub::temp_root("client_throttle");
+        let client = Client::new(&url, "abc", &root).with_min_interval(Duration::from_millis(200));
+        let start = Instant::now();
+        client.get(1, "").unwrap();
+        assert_eq!(client.get(1, "").unwrap().0, 404);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        server.join().unwrap();
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,412 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use crate::parse::ParseError;
+
+/// Why a parser gave up: what it expected at `at`, the input it could not go past.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure<'a> {
+    pub at: &'a str,
+    pub expected: String,
+}
+
+impl<'a> Failure<'a> {
+    pub fn new(at: &'a str, expected: impl Into<String>) -> Self {
+        Self {
+            at,
+            expected: expected.into(),
+        }
+    }
+
+    /// Keep the failure that got further, or both expectations if they stopped at the same
+    /// place.
+    fn merge(self, other: Self) -> Self {
+        match self.at.len().cmp(&other.at.len()) {
+            Ordering::Less => self,
+            Ordering::Greater => other,
+            Ordering::Equal => Self {
+                at: self.at,
+                expected: format!("{} or {}", self.expected, other.expected),
+            },
+        }
+    }
+
+    /// [`ParseError`] placed in `input`, of which `at` must be a slice.
+    pub fn into_error(self, day: u32, input: &str) -> ParseError {
+        // report the offending word, or the character if there is no word there
+        let word = self
+            .at
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(self.at.len());
+        let len = match word {
+            0 => self
+                .at
+                .chars()
+                .next()
+                .filter(|c| *c != '\n')
+                .map_or(0, char::len_utf8),
+            _ => word,
+        };
+        ParseError::within(day, input, &self.at[..len], self.expected)
+    }
+}
+
+/// Parsed value and the input left after it.
+pub type PResult<'a, T> = Result<(T, &'a str), Failure<'a>>;
+
+/// Something reading a `T` from the start of its input.
+///
+/// Implemented by functions and closures, and by tuples of parsers which run one after the
+/// other.
+pub trait Parser<'a, T> {
+    fn parse(&self, input: &'a str) -> PResult<'a, T>;
+}
+
+impl<'a, T, F: Fn(&'a str) -> PResult<'a, T>> Parser<'a, T> for F {
+    fn parse(&self, input: &'a str) -> PResult<'a, T> {
+        self(input)
+    }
+}
+
+macro_rules! tuple_parser {
+    ($($p:ident $t:ident $v:ident),+) => {
+        impl<'a, $($t, $p: Parser<'a, $t>),+> Parser<'a, ($($t,)+)> for ($($p,)+) {
+            fn parse(&self, input: &'a str) -> PResult<'a, ($($t,)+)> {
+                let ($($v,)+) = self;
+                let rest = input;
+                $(let ($v, rest) = $v.parse(rest)?;)+
+                Ok((($($v,)+), rest))
+            }
+        }
+    };
+}
+
+tuple_parser!(P1 T1 a, P2 T2 b);
+tuple_parser!(P1 T1 a, P2 T2 b, P3 T3 c);
+tuple_parser!(P1 T1 a, P2 T2 b, P3 T3 c, P4 T4 d);
+
+/// Run `parser` on the whole of `input`, only whitespace may be left after it.
+pub fn run<'a, T>(day: u32, input: &'a str, parser: impl Parser<'a, T>) -> Result<T, ParseError> {
+    whole(&parser, input, "the end of the input").map_err(|f| f.into_error(day, input))
+}
+
+/// Run `parser` on `input` and check nothing but whitespace is left.
+fn whole<'a, T>(
+    parser: &impl Parser<'a, T>,
+    input: &'a str,
+    expected: &str,
+) -> Result<T, Failure<'a>> {
+    let (value, rest) = parser.parse(input)?;
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        Ok(value)
+    } else {
+        Err(Failure::new(rest, expected))
+    }
+}
+
+/// Exactly `lit`.
+pub fn literal<'a>(lit: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: &'a str| match input.strip_prefix(lit) {
+        Some(rest) => Ok((&input[..lit.len()], rest)),
+        None => Err(Failure::new(input, format!("`{lit}`"))),
+    }
+}
+
+/// A character accepted by `pred`.
+pub fn satisfy<'a>(pred: impl Fn(char) -> bool, expected: &'static str) -> impl Parser<'a, char> {
+    move |input: &'a str| match input.chars().next() {
+        Some(c) if pred(c) => Ok((c, &input[c.len_utf8()..])),
+        _ => Err(Failure::new(input, expected)),
+    }
+}
+
+/// Spaces and tabs, possibly none, but not line breaks.
+pub fn spaces<'a>() -> impl Parser<'a, ()> {
+    |input: &'a str| Ok(((), input.trim_start_matches([' ', '\t'])))
+}
+
+/// Integer with an optional sign, read into any integer type.
+pub fn int<'a, T: FromStr>() -> impl Parser<'a, T> {
+    |input: &'a str| {
+        let sign = usize::from(input.starts_with(['-', '+']));
+        let digits = input[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.len() - sign);
+        let (number, rest) = input.split_at(sign + digits);
+        match number.parse() {
+            Ok(n) if digits > 0 => Ok((n, rest)),
+            _ => Err(Failure::new(input, "an integer")),
+        }
+    }
+}
+
+/// One or more ASCII letters or digits.
+pub fn word<'a>() -> impl Parser<'a, &'a str> {
+    |input: &'a str| {
+        let len = input
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(input.len());
+        match len {
+            0 => Err(Failure::new(input, "a word")),
+            _ => Ok(input.split_at(len)),
+        }
+    }
+}
+
+/// Spaces up to the end of the line, the line break is consumed.
+pub fn end_of_line<'a>() -> impl Parser<'a, ()> {
+    |input: &'a str| {
+        let rest = input.trim_start_matches([' ', '\t']);
+        match rest.strip_prefix('\n') {
+            Some(rest) => Ok(((), rest)),
+            None if rest.is_empty() => Ok(((), rest)),
+            None => Err(Failure::new(rest, "the end of the line")),
+        }
+    }
+}
+
+pub fn map<'a, T, U>(parser: impl Parser<'a, T>, f: impl Fn(T) -> U) -> impl Parser<'a, U> {
+    move |input: &'a str| parser.parse(input).map(|(value, rest)| (f(value), rest))
+}
+
+/// Say `expected` instead of what `parser` expected when it fails.
+pub fn label<'a, T>(parser: impl Parser<'a, T>, expected: &'static str) -> impl Parser<'a, T> {
+    move |input: &'a str| {
+        parser
+            .parse(input)
+            .map_err(|f| Failure::new(f.at, expected))
+    }
+}
+
+/// `Some` of what `parser` read, or `None` without reading anything if it fails.
+pub fn opt<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, Option<T>> {
+    move |input: &'a str| match parser.parse(input) {
+        Ok((value, rest)) => Ok((Some(value), rest)),
+        Err(_) => Ok((None, input)),
+    }
+}
+
+/// `first`, or `second` if `first` fails.
+pub fn or<'a, T>(first: impl Parser<'a, T>, second: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    move |input: &'a str| match first.parse(input) {
+        Ok(done) => Ok(done),
+        Err(failure) => second.parse(input).map_err(|f| failure.merge(f)),
+    }
+}
+
+/// `prefix` then `parser`, keeping what `parser` read.
+pub fn preceded<'a, A, T>(
+    prefix: impl Parser<'a, A>,
+    parser: impl Parser<'a, T>,
+) -> impl Parser<'a, T> {
+    map((prefix, parser), |(_, value)| value)
+}
+
+/// `parser` then `suffix`, keeping what `parser` read.
+pub fn terminated<'a, T, B>(
+    parser: impl Parser<'a, T>,
+    suffix: impl Parser<'a, B>,
+) -> impl Parser<'a, T> {
+    map((parser, suffix), |(value, _)| value)
+}
+
+/// `parser` as many times as it succeeds, possibly none.
+pub fn many<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |mut input: &'a str| {
+        let mut items = vec![];
+        while let Ok((item, rest)) = parser.parse(input) {
+            if rest.len() == input.len() {
+                break;
+            }
+            items.push(item);
+            input = rest;
+        }
+        Ok((items, input))
+    }
+}
+
+/// One or more `item` separated by `separator`. An `item` must follow every `separator`.
+pub fn separated<'a, T, S>(
+    item: impl Parser<'a, T>,
+    separator: impl Parser<'a, S>,
+) -> impl Parser<'a, Vec<T>> {
+    move |input: &'a str| {
+        let (first, mut input) = item.parse(input)?;
+        let mut items = vec![first];
+        while let Ok((_, rest)) = separator.parse(input) {
+            let (next, rest) = item.parse(rest)?;
+            items.push(next);
+            input = rest;
+        }
+        Ok((items, input))
+    }
+}
+
+/// `key: value` on a line of its own, indentation allowed.
+pub fn field<'a, T>(key: &'static str, value: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    preceded(
+        (spaces(), literal(key), literal(":"), spaces()),
+        terminated(value, end_of_line()),
+    )
+}
+
+/// `item` on every non blank line left, leading and trailing spaces ignored. Each `item` must
+/// read its whole line.
+pub fn lines<'a, T>(item: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |mut input: &'a str| {
+        let mut items = vec![];
+        while !input.is_empty() {
+            let (line, rest) = input.split_once('\n').unwrap_or((input, ""));
+            let line = line.trim();
+            if !line.is_empty() {
+                items.push(whole(&item, line, "the end of the line")?);
+            }
+            input = rest;
+        }
+        Ok((items, input))
+    }
+}
+
+/// Skip the lines holding only whitespace.
+fn skip_blank_lines(mut input: &str) -> &str {
+    while let Some((line, rest)) = input.split_once('\n') {
+        if !line.trim().is_empty() {
+            return input;
+        }
+        input = rest;
+    }
+    if input.trim().is_empty() {
+        &input[input.len()..]
+    } else {
+        input
+    }
+}
+
+/// `item` on the next block of lines, ended by a blank line. Leading blank lines are skipped
+/// but the indentation of the first line is kept. `item` must read the whole block.
+pub fn block<'a, T>(item: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    move |input: &'a str| {
+        let input = skip_blank_lines(input);
+        let len = input
+            .split_inclusive('\n')
+            .take_while(|line| !line.trim().is_empty())
+            .map(str::len)
+            .sum();
+        let (block, rest) = input.split_at(len);
+        Ok((whole(&item, block, "the end of the block")?, rest))
+    }
+}
+
+/// `item` on every block left, see [`block`].
+pub fn blocks<'a, T>(item: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    let block = block(item);
+    move |mut input: &'a str| {
+        let mut items = vec![];
+        while !skip_blank_lines(input).is_empty() {
+            let (item, rest) = block.parse(input)?;
+            items.push(item);
+            input = rest;
+        }
+        Ok((items, input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(int::<i32>().parse("-12,3"), Ok((-12, ",3")));
+        assert_eq!(int::<u8>().parse("+7"), Ok((7, "")));
+        assert!(int::<u8>().parse("300").is_err());
+        assert!(int::<i32>().parse("-").is_err());
+        assert!(int::<u32>().parse("-1").is_err());
+        assert_eq!(word().parse("AA, BB"), Ok(("AA", ", BB")));
+        assert_eq!(literal("x=").parse("x=1"), Ok(("x=", "1")));
+        assert_eq!(
+            or(literal("a"), literal("b")).parse("c"),
+            Err(Failure::new("c", "`a` or `b`"))
+        );
+        assert_eq!(end_of_line().parse("  \nnext"), Ok(((), "next")));
+        assert_eq!(opt(literal("-")).parse("5"), Ok((None, "5")));
+        let point = (
+            preceded(literal("x="), int::<i64>()),
+            preceded(literal(", y="), int()),
+        );
+        assert_eq!(point.parse("x=-2, y=15:"), Ok(((-2, 15), ":")));
+    }
+
+    #[test]
+    fn test_lists() {
+        let numbers = separated(int::<u32>(), literal(", "));
+        assert_eq!(numbers.parse("1, 2, 3;"), Ok((vec![1, 2, 3], ";")));
+        // a separator commits to another item
+        assert_eq!(numbers.parse("1, x").unwrap_err().at, "x");
+        assert_eq!(
+            many(literal("ab")).parse("ababa"),
+            Ok((vec!["ab", "ab"], "a"))
+        );
+
+        let input = "\n  1 2\n\n 3\n";
+        let parsed = run(0, input, lines(separated(int::<u32>(), literal(" "))));
+        assert_eq!(parsed, Ok(vec![vec![1, 2], vec![3]]));
+        let error = run(0, input, lines(int::<u32>())).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(5)));
+        assert_eq!(error.expected, "the end of the line");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = "\n  a: 1\n  b: -2\n\n\n  a: 3\n  b: 4\n";
+        let pair = (field("a", int::<i32>()), field("b", int::<i32>()));
+        assert_eq!(run(0, input, blocks(pair)), Ok(vec![(1, -2), (3, 4)]));
+
+        // the first line keeps its indentation
+        let indented = (block((many(literal(" ")), word())), block(word()));
+        let parsed = run(0, "\n   \n\n  x\n\ny", indented);
+        assert_eq!(parsed, Ok(((vec![" ", " "], "x"), "y")));
+        let error = run(0, "a\n\nb c\n", blocks(word())).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), Some(3)));
+        assert_eq!(error.expected, "the end of the block");
+    }
+
+    #[test]
+    fn test_errors() {
+        let input = "a: 1\nb: x\n";
+        let pair = (field("a", int::<i32>()), field("b", int::<i32>()));
+        let error = run(3, input, pair).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 3, line 2, column 4: expected an integer, found `x`"
+        );
+        let error = run(
+            3,
+            "a: 1\nc: 2",
+            (field("a", int::<i32>()), field("b", int::<i32>())),
+        )
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(1)));
+        assert_eq!(error.expected, "`b`");
+        let error = run(
+            3,
+            "a: 1",
+            (field("a", int::<i32>()), field("b", int::<i32>())),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 3, line 1, column 5: expected `b`, found nothing"
+        );
+        let error = run(3, "7 8", label(int::<u8>(), "a count")).unwrap_err();
+        assert_eq!(
+            (error.column, error.expected.as_str()),
+            (Some(3), "the end of the input")
+        );
+        let error = run(3, "-7", label(int::<u8>(), "a count")).unwrap_err();
+        assert_eq!(
+            (error.found.as_str(), error.expected.as_str()),
+            ("-7", "a count")
+        );
+    }
+}
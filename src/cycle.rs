@@ -0,0 +1,106 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Where a sequence of states starts repeating: the state after `start + period` steps is the
+/// same as after `start` steps, and so on forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Steps before the first state that repeats.
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Steps taken when the repetition was noticed.
+    pub fn end(&self) -> usize {
+        self.start + self.period
+    }
+
+    /// Whole periods skipped to reach `step`, and the step before `end` that is in the same
+    /// state.
+    pub fn fast_forward(&self, step: usize) -> (usize, usize) {
+        if step < self.start {
+            return (0, step);
+        }
+        let offset = step - self.start;
+        (offset / self.period, self.start + offset % self.period)
+    }
+
+    /// Value after `step` steps of a metric that grows by the same amount every period.
+    /// `metric(i)` is its value after `i` steps and is only asked for `i <= end`.
+    pub fn extrapolate(&self, step: usize, metric: impl Fn(usize) -> i64) -> i64 {
+        let (periods, same) = self.fast_forward(step);
+        let growth = metric(self.end()) - metric(self.start);
+        metric(same) + periods as i64 * growth
+    }
+}
+
+/// Apply `step` to `state` until `key` gives a value it already gave, at most `limit` times.
+///
+/// `key` may keep only what decides the future of the state, e.g. the top of a tower rather
+/// than all of it. `None` if nothing repeated, `state` has then taken `limit` steps.
+pub fn find_cycle<S, K: Hash + Eq>(
+    state: &mut S,
+    mut key: impl FnMut(&S) -> K,
+    mut step: impl FnMut(&mut S),
+    limit: usize,
+) -> Option<Cycle> {
+    let mut seen = HashMap::from([(key(state), 0)]);
+    for steps in 1..=limit {
+        step(state);
+        if let Some(start) = seen.insert(key(state), steps) {
+            return Some(Cycle {
+                start,
+                period: steps - start,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_cycle() {
+        // 3, 10, 5, 16, 8, 4, 2, 1, 4, ...
+        let mut n = 3u64;
+        let collatz = |n: &mut u64| {
+            *n = if n.is_multiple_of(2) {
+                *n / 2
+            } else {
+                3 * *n + 1
+            }
+        };
+        let cycle = find_cycle(&mut n, |n| *n, collatz, 100).unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 5,
+                period: 3
+            }
+        );
+        assert_eq!(n, 4);
+        assert_eq!(find_cycle(&mut 27u64, |n| *n, collatz, 10), None);
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        // counts the steps taken, so the metric is the step itself
+        let cycle = Cycle {
+            start: 4,
+            period: 6,
+        };
+        assert_eq!(cycle.fast_forward(3), (0, 3));
+        assert_eq!(cycle.fast_forward(4), (0, 4));
+        assert_eq!(cycle.fast_forward(23), (3, 5));
+        assert_eq!(
+            cycle.extrapolate(1_000_000_000_000, |i| i as i64),
+            1_000_000_000_000
+        );
+        // a metric only growing outside the prefix
+        let metric = |i: usize| i.max(4) as i64 * 2;
+        assert_eq!(cycle.extrapolate(2, metric), 8);
+        assert_eq!(cycle.extrapolate(100, metric), 200);
+    }
+}
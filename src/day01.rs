@@ -1,42 +1,49 @@
-pub fn run() {
-    let input = include_str!("../input/day01/first");
-    dbg!(first(input));
-    dbg!(second(input));
+use crate::{Answer, Solver, bench::parsing};
+
+pub struct Day01;
+
+impl Solver for Day01 {
+    const DAY: u32 = 1;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
+    }
+}
+
+/// Total calories carried by each elf.
+fn to_calories(input: &str) -> Vec<usize> {
+    parsing(|| {
+        input
+            .split("\n\n")
+            .map(|i| {
+                i.split('\n')
+                    .filter(|j| !j.is_empty())
+                    .map(|k| k.trim())
+                    .map(|j| j.parse::<usize>().unwrap())
+                    .sum()
+            })
+            .collect()
+    })
 }
 
 fn first(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .map(|i| {
-            i.split('\n')
-                .filter(|j| !j.is_empty())
-                .map(|k| k.trim())
-                .map(|j| j.parse::<usize>().unwrap())
-                .sum()
-        })
-        .max()
-        .unwrap()
+    to_calories(input).into_iter().max().unwrap()
 }
 
 fn second(input: &str) -> usize {
-    let mut calories: Vec<usize> = input
-        .split("\n\n")
-        .map(|i| {
-            i.split('\n')
-                .filter(|j| !j.is_empty())
-                .map(|k| k.trim())
-                .map(|j| j.parse::<usize>().unwrap())
-                .sum()
-        })
-        .collect();
+    let mut calories = to_calories(input);
     calories.sort_by(|a, b| b.cmp(a));
-    dbg!(&calories);
     calories.iter().take(3).sum()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     static INPUT: &str = "
     1000
@@ -62,4 +69,11 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 45000);
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day01.run_part1(&input), Answer::Integer(24000));
+        assert_eq!(Day01.run_part2(&input), Answer::Integer(45000));
+    }
 }
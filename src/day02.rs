@@ -1,9 +1,23 @@
-use std::{cmp::Ordering, error::Error, fmt, str::FromStr};
+use std::{cmp::Ordering, str::FromStr};
 
-pub fn run() {
-    let input = include_str!("../input/day02/first");
-    dbg!(first(input));
-    dbg!(second(input));
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    parse::{self, ParseError},
+};
+
+pub struct Day02;
+
+impl Solver for Day02 {
+    const DAY: u32 = 2;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -63,28 +77,8 @@ impl Choice {
     }
 }
 
-/// An error returned when parsing a `bool` using [`from_str`] fails
-///
-/// [`from_str`]: super::FromStr::from_str
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ParseChoiceError;
-
-impl fmt::Display for ParseChoiceError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        "provided string was not in allow list".fmt(f)
-    }
-}
-
-impl Error for ParseChoiceError {
-    #[allow(deprecated)]
-    fn description(&self) -> &str {
-        "failed to parse bool"
-    }
-}
-
 impl FromStr for Choice {
-    type Err = ParseChoiceError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -96,45 +90,48 @@ impl FromStr for Choice {
             "Y" => Ok(Self::Paper),
             "Z" => Ok(Self::Scissors),
 
-            _ => Err(ParseChoiceError),
+            _ => Err(ParseError::new(
+                Day02::DAY,
+                "`A`, `B`, `C`, `X`, `Y` or `Z`",
+                s,
+            )),
         }
     }
 }
 
-fn first(input: &str) -> usize {
-    input
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|i| {
-            let pair = i
-                .trim()
+/// Both columns of the strategy guide, the second one read as a choice.
+fn to_rounds(input: &str) -> Vec<(Choice, Choice)> {
+    parsing(|| {
+        parse::or_panic(parse::each_line(input, |line| {
+            let mut choices = line
                 .split_ascii_whitespace()
-                .take(2)
-                .collect::<Vec<&str>>();
-            let left: Choice = pair[0].parse().unwrap();
-            let right: Choice = pair[1].parse().unwrap();
-            (left, right)
-        })
+                .map(|c| c.parse::<Choice>().map_err(|e| e.rebase(line, c)));
+            let mut next = || {
+                choices
+                    .next()
+                    .unwrap_or_else(|| Err(ParseError::end_of(Day02::DAY, line, "a choice")))
+            };
+            Ok((next()?, next()?))
+        }))
+    })
+}
+
+fn first(input: &str) -> usize {
+    to_rounds(input)
+        .into_iter()
         .map(|(left, right)| right.compare(&left))
         .sum()
 }
 
 fn second(input: &str) -> usize {
-    input
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|i| {
-            let pair = i
-                .trim()
-                .split_ascii_whitespace()
-                .take(2)
-                .collect::<Vec<&str>>();
-            let left: Choice = pair[0].parse().unwrap();
-            let right = match pair[1] {
-                "X" => left.less(),
-                "Y" => left,
-                "Z" => left.greater(),
-                _ => unreachable!(),
+    to_rounds(input)
+        .into_iter()
+        .map(|(left, outcome)| {
+            // X, Y and Z mean lose, draw and win
+            let right = match outcome {
+                Choice::Rock => left.less(),
+                Choice::Paper => left,
+                Choice::Scissors => left.greater(),
             };
             (left, right)
         })
@@ -145,6 +142,7 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     static INPUT: &str = "
     A Y
@@ -160,4 +158,11 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 12);
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day02.run_part1(&input), Answer::Integer(15));
+        assert_eq!(Day02.run_part2(&input), Answer::Integer(12));
+    }
 }
@@ -1,21 +1,39 @@
 use std::collections::HashSet;
 
-pub fn run() {
-    let input = include_str!("../input/day03/first");
-    dbg!(first(input));
-    dbg!(second(input));
+use crate::{Answer, Solver, bench::parsing};
+
+pub struct Day03;
+
+impl Solver for Day03 {
+    const DAY: u32 = 3;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
+    }
+}
+
+fn to_rucksacks(input: &str) -> Vec<&str> {
+    parsing(|| {
+        input
+            .split('\n')
+            .map(|k| k.trim())
+            .filter(|j| !j.is_empty())
+            .collect()
+    })
 }
 
 fn first(input: &str) -> usize {
-    input
-        .split('\n')
-        .map(|k| k.trim())
-        .filter(|j| !j.is_empty())
+    to_rucksacks(input)
+        .into_iter()
         .map(|j| {
             let split_idx = j.len() / 2;
             let left: HashSet<char> = j[0..split_idx].chars().collect();
             let right: HashSet<char> = j[split_idx..].chars().collect();
-            let c = left.intersection(&right).into_iter().next().unwrap();
+            let c = left.intersection(&right).next().unwrap();
             c.to_owned()
         })
         .map(|c| match c {
@@ -27,11 +45,7 @@ fn first(input: &str) -> usize {
 }
 
 fn second(input: &str) -> usize {
-    let groups: Vec<&str> = input
-        .split('\n')
-        .map(|k| k.trim())
-        .filter(|j| !j.is_empty())
-        .collect();
+    let groups = to_rucksacks(input);
 
     groups
         .chunks(3)
@@ -54,6 +68,7 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     static INPUT: &str = "
         vJrwpWtwJgWrhcsFMMfFFhFp
@@ -73,4 +88,11 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 70);
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day03.run_part1(&input), Answer::Integer(157));
+        assert_eq!(Day03.run_part2(&input), Answer::Integer(70));
+    }
 }
@@ -1,9 +1,24 @@
-use std::{num::ParseIntError, str::FromStr};
+use std::{ops::RangeInclusive, str::FromStr};
 
-pub fn run() {
-    let input = include_str!("../input/day04/first");
-    dbg!(first(input));
-    dbg!(second(input));
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    interval::IntervalSet,
+    parse::{self, ParseError},
+};
+
+pub struct Day04;
+
+impl Solver for Day04 {
+    const DAY: u32 = 4;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -13,55 +28,58 @@ pub struct Range {
 }
 
 impl FromStr for Range {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let num: Vec<&str> = s.split('-').collect();
-        let start = num[0].parse()?;
-        let end = num[1].parse()?;
-        Ok(Self { start, end })
+        let Some((start, end)) = s.split_once('-') else {
+            return Err(ParseError::end_of(Day04::DAY, s, "`-`"));
+        };
+        let section =
+            |n: &str| parse::token(Day04::DAY, n, "a section").map_err(|e| e.rebase(s, n));
+        Ok(Self {
+            start: section(start)?,
+            end: section(end)?,
+        })
     }
 }
 
 impl Range {
+    fn sections(&self) -> RangeInclusive<i64> {
+        self.start as i64..=self.end as i64
+    }
+
     fn contains(&self, other: &Self) -> bool {
-        (self.start <= other.start) && (self.end >= other.end)
+        IntervalSet::from(self.sections()).contains_range(other.sections())
     }
 
     fn overlap(&self, other: &Self) -> bool {
-        (self.start >= other.start && self.start <= other.end)
-            || (self.end >= other.start && self.end <= other.end)
-            || self.contains(other)
-            || other.contains(self)
+        let mine = IntervalSet::from(self.sections());
+        !mine.intersection(&other.sections().into()).is_empty()
     }
 }
 
+fn to_pairs(input: &str) -> Vec<(Range, Range)> {
+    parsing(|| {
+        parse::or_panic(parse::each_line(input, |line| {
+            let Some((left, right)) = line.split_once(',') else {
+                return Err(ParseError::end_of(Day04::DAY, line, "`,`"));
+            };
+            let range = |r: &str| r.parse::<Range>().map_err(|e| e.rebase(line, r));
+            Ok((range(left)?, range(right)?))
+        }))
+    })
+}
+
 fn first(input: &str) -> usize {
-    input
-        .split('\n')
-        .map(|k| k.trim())
-        .filter(|j| !j.is_empty())
-        .map(|i| {
-            let pairs: Vec<&str> = i.split(',').take(2).collect();
-            let left: Range = pairs[0].parse().unwrap();
-            let right: Range = pairs[1].parse().unwrap();
-            (left, right)
-        })
+    to_pairs(input)
+        .into_iter()
         .filter(|(left, right)| left.contains(right) || right.contains(left))
         .count()
 }
 
 fn second(input: &str) -> usize {
-    input
-        .split('\n')
-        .map(|k| k.trim())
-        .filter(|j| !j.is_empty())
-        .map(|i| {
-            let pairs: Vec<&str> = i.split(',').take(2).collect();
-            let left: Range = pairs[0].parse().unwrap();
-            let right: Range = pairs[1].parse().unwrap();
-            (left, right)
-        })
+    to_pairs(input)
+        .into_iter()
         .filter(|(left, right)| left.overlap(right))
         .count()
 }
@@ -69,6 +87,7 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     static INPUT: &str = "
         2-4,6-8
@@ -88,4 +107,11 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 4);
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day04.run_part1(&input), Answer::Integer(2));
+        assert_eq!(Day04.run_part2(&input), Answer::Integer(4));
+    }
 }
@@ -1,13 +1,27 @@
-use std::{
-    num::ParseIntError,
-    ops::{Deref, DerefMut},
-    str::FromStr,
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    combinator::{
+        self, Parser, block, end_of_line, int, lines, literal, many, map, opt, or, preceded,
+        satisfy, separated, spaces, terminated,
+    },
+    parse,
 };
 
-pub fn run() {
-    let input = include_str!("../input/day05/input");
-    dbg!(first(input));
-    dbg!(second(input));
+pub struct Day05;
+
+impl Solver for Day05 {
+    const DAY: u32 = 5;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
+    }
 }
 
 #[derive(Default, Debug, Clone)]
@@ -44,80 +58,87 @@ impl DerefMut for Crate {
     }
 }
 
+/// "move 1 from 2 to 1", stacks counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    count: usize,
+    from: usize,
+    to: usize,
+}
+
 impl Crate {
-    fn move_action(&mut self, action: &str) {
-        // "move 1 from 2 to 1"
-        let splited: Vec<&str> = action.split_whitespace().collect();
-        let (num, from, to) = (
-            splited[1].parse::<usize>().unwrap(),
-            splited[3].parse::<usize>().unwrap(),
-            splited[5].parse::<usize>().unwrap(),
-        );
-        for _ in 0..num {
-            let need_move = self.0[from - 1].pop().unwrap();
-            self.0[to - 1].push(need_move);
+    fn move_action(&mut self, action: &Move) {
+        for _ in 0..action.count {
+            let need_move = self.0[action.from - 1].pop().unwrap();
+            self.0[action.to - 1].push(need_move);
         }
     }
 
-    fn new_move_action(&mut self, action: &str) {
-        // "move 1 from 2 to 1"
-        let splited: Vec<&str> = action.split_whitespace().collect();
-        let (num, from, to) = (
-            splited[1].parse::<usize>().unwrap(),
-            splited[3].parse::<usize>().unwrap(),
-            splited[5].parse::<usize>().unwrap(),
-        );
-        let need_moved = self.0[from - 1][(self.0[from - 1].len() - num)..].to_owned();
+    fn new_move_action(&mut self, action: &Move) {
+        let from = &self.0[action.from - 1];
+        let need_moved = from[(from.len() - action.count)..].to_owned();
         for c in need_moved {
-            self.0[from - 1].pop();
-            self.0[to - 1].push(c);
+            self.0[action.from - 1].pop();
+            self.0[action.to - 1].push(c);
         }
     }
 }
 
-impl FromStr for Crate {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lines: Vec<&str> = s.split('\n').filter(|l| !l.is_empty()).collect();
-        let stack_count: usize = lines
-            .last()
-            .unwrap()
-            .split_ascii_whitespace()
-            .last()
-            .unwrap()
-            .parse()
-            .unwrap();
-        dbg!(stack_count);
-        let mut _crate = Crate::default();
-        for i in 0..stack_count {
-            let mut stack = Stack::default();
-            for line in lines.iter().rev() {
-                if let Some(c) = line.char_indices().nth(i * 4 + 1) {
-                    if c.1.is_ascii_uppercase() {
-                        stack.push(c.1);
-                    }
-                }
+/// `[X]` for a crate, three spaces for none.
+fn slot<'a>() -> impl Parser<'a, Option<char>> {
+    or(
+        map(
+            (
+                literal("["),
+                satisfy(|c| c.is_ascii_uppercase(), "a crate letter"),
+                literal("]"),
+            ),
+            |(_, c, _)| Some(c),
+        ),
+        map(literal("   "), |_| None),
+    )
+}
+
+/// Rows of crates, top first, over a line numbering the stacks.
+fn drawing<'a>() -> impl Parser<'a, Crate> {
+    let row = terminated(many(terminated(slot(), opt(literal(" ")))), end_of_line());
+    let numbers = preceded(spaces(), separated(int::<usize>(), literal("   ")));
+    map((many(row), numbers), |(rows, numbers)| {
+        let width = rows.iter().map(Vec::len).fold(numbers.len(), usize::max);
+        let mut stacks = vec![Stack::default(); width];
+        for row in rows.iter().rev() {
+            for (stack, c) in stacks.iter_mut().zip(row) {
+                stack.extend(c);
             }
-            _crate.push(stack);
         }
-        Ok(_crate)
-    }
+        Crate(stacks)
+    })
+}
+
+fn action<'a>() -> impl Parser<'a, Move> {
+    map(
+        (
+            preceded(literal("move "), int()),
+            preceded(literal(" from "), int()),
+            preceded(literal(" to "), int()),
+        ),
+        |(count, from, to)| Move { count, from, to },
+    )
+}
+
+fn to_crate_and_moves(input: &str) -> (Crate, Vec<Move>) {
+    parsing(|| {
+        parse::or_panic(combinator::run(
+            Day05::DAY,
+            input,
+            (block(drawing()), lines(action())),
+        ))
+    })
 }
 
 fn first(input: &str) -> String {
-    let splited: Vec<&str> = input.split("\n\n").collect();
-    dbg!(&splited[0]);
-    let mut _crate: Crate = splited[0].parse().unwrap();
-    dbg!(&_crate);
-
-    let moves: Vec<&str> = splited[1]
-        .split('\n')
-        .map(|k| k.trim())
-        .filter(|j| !j.is_empty())
-        .collect();
-
-    for action in moves {
+    let (mut _crate, moves) = to_crate_and_moves(input);
+    for action in &moves {
         _crate.move_action(action);
     }
 
@@ -130,18 +151,8 @@ fn first(input: &str) -> String {
 }
 
 fn second(input: &str) -> String {
-    let splited: Vec<&str> = input.split("\n\n").collect();
-    dbg!(&splited[0]);
-    let mut _crate: Crate = splited[0].parse().unwrap();
-    dbg!(&_crate);
-
-    let moves: Vec<&str> = splited[1]
-        .split('\n')
-        .map(|k| k.trim())
-        .filter(|j| !j.is_empty())
-        .collect();
-
-    for action in moves {
+    let (mut _crate, moves) = to_crate_and_moves(input);
+    for action in &moves {
         _crate.new_move_action(action);
     }
 
@@ -156,6 +167,7 @@ fn second(input: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     static INPUT: &str = "
     [D]
@@ -178,4 +190,31 @@ move 1 from 1 to 2
     fn test_second() {
         assert_eq!(second(INPUT), "MCD");
     }
+
+    #[test]
+    fn test_parse() {
+        let (_crate, moves) = to_crate_and_moves(INPUT);
+        let stacks: Vec<String> = _crate.iter().map(|s| s.iter().collect()).collect();
+        assert_eq!(stacks, ["ZN", "MCD", "P"]);
+        assert_eq!(
+            moves[1],
+            Move {
+                count: 3,
+                from: 1,
+                to: 3
+            }
+        );
+        let error = combinator::run(Day05::DAY, "move 1 from x to 2", action()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 5, line 1, column 13: expected an integer, found `x`"
+        );
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day05.run_part1(&input), "CMZ");
+        assert_eq!(Day05.run_part2(&input), "MCD");
+    }
 }
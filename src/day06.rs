@@ -1,14 +1,24 @@
 use std::collections::HashSet;
 
-pub fn run() {
-    let input = include_str!("../input/day06/input").trim();
-    dbg!(first(input));
-    dbg!(second(input));
+use crate::{Answer, Solver, bench::parsing};
+
+pub struct Day06;
+
+impl Solver for Day06 {
+    const DAY: u32 = 6;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input.trim()).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input.trim()).into()
+    }
 }
 
 fn first(input: &str) -> usize {
     let marker = 4;
-    let test: Vec<char> = input.chars().collect();
+    let test: Vec<char> = parsing(|| input.chars().collect());
     for (idx, item) in test.windows(marker).enumerate() {
         let set: HashSet<char> = HashSet::from_iter(item.iter().copied());
         if set.len() == marker {
@@ -20,7 +30,7 @@ fn first(input: &str) -> usize {
 
 fn second(input: &str) -> usize {
     let marker = 14;
-    let test: Vec<char> = input.chars().collect();
+    let test: Vec<char> = parsing(|| input.chars().collect());
     for (idx, item) in test.windows(marker).enumerate() {
         let set: HashSet<char> = HashSet::from_iter(item.iter().copied());
         if set.len() == marker {
@@ -33,6 +43,7 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     #[test]
     fn test_first() {
@@ -50,4 +61,16 @@ mod tests {
         assert_eq!(second("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 29);
         assert_eq!(second("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 26);
     }
+
+    #[test]
+    fn test_crlf() {
+        assert_eq!(
+            Day06.run_part1(&crlf("bvwbjplbgvbhsrlpgdmjqwftvncz\n")),
+            Answer::Integer(5)
+        );
+        assert_eq!(
+            Day06.run_part2(&crlf("mjqjpqmgbljsphdztnvjfqwrcgsmlb\n")),
+            Answer::Integer(19)
+        );
+    }
 }
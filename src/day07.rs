@@ -1,14 +1,27 @@
 use std::{
     collections::HashMap,
-    error::Error,
     fmt::{self, Display},
     str::FromStr,
 };
 
-pub fn run() {
-    let input = include_str!("../input/day07/input").trim();
-    dbg!(first(input));
-    dbg!(second(input));
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    parse::{self, ParseError},
+};
+
+pub struct Day07;
+
+impl Solver for Day07 {
+    const DAY: u32 = 7;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input.trim()).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input.trim()).into()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -135,35 +148,24 @@ pub enum Cmd {
     LS,
 }
 
-/// An error returned when parsing a `bool` using [`from_str`] fails
-///
-/// [`from_str`]: super::FromStr::from_str
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ParseChoiceError;
-
-impl fmt::Display for ParseChoiceError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        "provided string was not in allow list".fmt(f)
-    }
-}
-
-impl Error for ParseChoiceError {
-    #[allow(deprecated)]
-    fn description(&self) -> &str {
-        "failed to parse cmd"
-    }
-}
-
 impl FromStr for Cmd {
-    type Err = ParseChoiceError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lst: Vec<&str> = s.split_whitespace().collect();
-        match lst[1] {
+        let mut words = s.split_whitespace();
+        let mut next = |expected| {
+            words
+                .next()
+                .ok_or_else(|| ParseError::end_of(Day07::DAY, s, expected))
+        };
+        let prompt = next("`$`")?;
+        if prompt != "$" {
+            return Err(ParseError::within(Day07::DAY, s, prompt, "`$`"));
+        }
+        match next("a command")? {
             "ls" => Ok(Self::LS),
-            "cd" => Ok(Self::CD(lst[2].to_string())),
-            _ => Err(ParseChoiceError),
+            "cd" => Ok(Self::CD(next("a directory")?.to_string())),
+            cmd => Err(ParseError::within(Day07::DAY, s, cmd, "`ls` or `cd`")),
         }
     }
 }
@@ -187,7 +189,9 @@ fn get_dirs(input: &str) -> Directory {
         .collect();
     for (idx, line) in lines.iter().enumerate() {
         if line.starts_with('$') {
-            let cmd: Cmd = line.parse().unwrap();
+            let cmd: Cmd = line
+                .parse()
+                .unwrap_or_else(|e: ParseError| panic!("{}", e.rebase(input, line)));
             match cmd {
                 Cmd::LS => {
                     let path: Vec<&str> = current_path.iter().map(|s| s.as_str()).collect();
@@ -200,7 +204,9 @@ fn get_dirs(input: &str) -> Directory {
                                 current_dir.push_dir(current_dir.new_dir(splited[1]));
                             }
                         } else {
-                            let (name, size) = (splited[1], splited[0].parse::<usize>().unwrap());
+                            let size = parse::token(Day07::DAY, splited[0], "a file size")
+                                .unwrap_or_else(|e| panic!("{}", e.rebase(input, splited[0])));
+                            let name = splited[1];
                             current_dir.files.entry(name.to_string()).or_insert(size);
                         }
                     }
@@ -224,7 +230,7 @@ fn get_dirs(input: &str) -> Directory {
 }
 
 fn first(input: &str) -> usize {
-    let root = get_dirs(input);
+    let root = parsing(|| get_dirs(input));
     root.total_size_vec()
         .into_iter()
         .filter(|i| *i <= 100000)
@@ -232,7 +238,7 @@ fn first(input: &str) -> usize {
 }
 
 fn second(input: &str) -> usize {
-    let root = get_dirs(input);
+    let root = parsing(|| get_dirs(input));
     let mut total = root.total_size_vec();
     total.sort();
     let root_total = root.total_size();
@@ -248,6 +254,7 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     static INPUT: &str = r#"
         $ cd /
@@ -283,4 +290,11 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 24933642);
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day07.run_part1(&input), Answer::Integer(95437));
+        assert_eq!(Day07.run_part2(&input), Answer::Integer(24933642));
+    }
 }
@@ -1,116 +1,56 @@
-use std::{error::Error, fmt, ops::Deref, str::FromStr};
+use std::{fmt, ops::Deref, str::FromStr};
 
-pub fn run() {
-    let input = include_str!("../input/day08/input");
-    dbg!(first(input));
-    dbg!(second(input));
-}
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    geometry::{Direction, Point},
+    grid,
+    parse::{self, ParseError},
+    render::{Render, Rgb},
+};
 
-pub const ALL_DIRECTIONS: [Direction; 4] = [
-    Direction::Top,
-    Direction::Down,
-    Direction::Left,
-    Direction::Right,
-];
-
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-pub struct Position {
-    x: usize,
-    y: usize,
-}
+pub struct Day08;
 
-impl Position {
-    pub fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
-}
+impl Solver for Day08 {
+    const DAY: u32 = 8;
 
-impl From<(usize, usize)> for Position {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
     }
-}
 
-impl From<&(usize, usize)> for Position {
-    fn from(value: &(usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Direction {
-    Top,
-    Down,
-    Left,
-    Right,
-}
+type Position = Point<usize>;
 
+/// Tree heights.
 #[derive(Debug, Clone)]
-pub struct Grid(Vec<Vec<u8>>);
+pub struct Grid(grid::Grid<u8>);
 
 impl Grid {
-    #[inline]
-    fn row_len(&self) -> usize {
-        self.0[0].len()
-    }
-
-    #[inline]
-    fn col_len(&self) -> usize {
-        self.0.len()
-    }
-
-    #[inline]
-    fn pos_item(&self, pos: impl Into<Position>) -> u8 {
-        let p = pos.into();
-        self.0[p.y][p.x]
+    fn is_edge(&self, pos: &Position) -> bool {
+        pos.x == 0 || pos.x == self.width() - 1 || pos.y == 0 || pos.y == self.height() - 1
     }
 
     fn neighbors(&self, pos: &Position, direct: Direction) -> Vec<u8> {
-        match direct {
-            Direction::Top => (0..pos.y)
-                .into_iter()
-                .map(|i| self.pos_item((pos.x, i)))
-                .rev()
-                .collect(),
-            Direction::Down => ((pos.y + 1)..self.col_len())
-                .into_iter()
-                .map(|i| self.pos_item((pos.x, i)))
-                .collect(),
-            Direction::Left => (0..pos.x)
-                .into_iter()
-                .map(|i| self.pos_item((i, pos.y)))
-                .rev()
-                .collect(),
-            Direction::Right => ((pos.x + 1)..self.row_len())
-                .into_iter()
-                .map(|i| self.pos_item((i, pos.y)))
-                .collect(),
-        }
+        self.0.ray(*pos, direct).map(|(_, &h)| h).collect()
     }
 
     fn is_visible(&self, pos: &Position) -> bool {
-        if pos.x == 0
-            || (pos.x == self.row_len() - 1)
-            || pos.y == 0
-            || (pos.y == self.col_len() - 1)
-        {
+        if self.is_edge(pos) {
             return true;
         }
-        let item = self.pos_item(*pos);
-        ALL_DIRECTIONS
+        let item = self[*pos];
+        Direction::ALL
             .into_iter()
-            .any(|direct| self.neighbors(pos, direct).into_iter().all(|i| i < item))
+            .any(|direct| self.0.ray(*pos, direct).all(|(_, &i)| i < item))
     }
 
     fn scenic_score(&self, pos: &Position) -> usize {
-        let item = self.pos_item(*pos);
-        ALL_DIRECTIONS
+        let item = self[*pos];
+        Direction::ALL
             .into_iter()
             .map(|direct| {
                 let ns = self.neighbors(pos, direct);
@@ -121,31 +61,20 @@ impl Grid {
     }
 
     fn all_visible_count(&self) -> usize {
-        (0..self.col_len())
-            .into_iter()
-            .flat_map(move |x| {
-                (0..self.row_len())
-                    .into_iter()
-                    .filter(move |y| self.is_visible(&(x, *y).into()))
-            })
-            .count()
+        self.positions().filter(|p| self.is_visible(p)).count()
     }
 
     fn highest_scenic_score(&self) -> usize {
-        (1..(self.col_len() - 1))
-            .into_iter()
-            .flat_map(move |x| {
-                (1..(self.row_len() - 1))
-                    .into_iter()
-                    .map(move |y| self.scenic_score(&(x, y).into()))
-            })
+        self.positions()
+            .filter(|p| !self.is_edge(p))
+            .map(|p| self.scenic_score(&p))
             .max()
             .unwrap()
     }
 }
 
 impl Deref for Grid {
-    type Target = Vec<Vec<u8>>;
+    type Target = grid::Grid<u8>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -154,67 +83,51 @@ impl Deref for Grid {
 
 impl fmt::Display for Grid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in &self.0 {
-            for col in row {
-                write!(f, "{col}")?;
-            }
-            "\n".fmt(f)?;
-        }
-        Ok(())
+        self.0.display(|&h| char::from(b'0' + h)).fmt(f)
     }
 }
 
-/// An error returned when parsing a `bool` using [`from_str`] fails
-///
-/// [`from_str`]: super::FromStr::from_str
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ParseGridError;
-
-impl fmt::Display for ParseGridError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        "provided string was not in allow list".fmt(f)
+/// Tree heights from dark to light green.
+impl Render for Grid {
+    fn size(&self) -> (usize, usize) {
+        (self.width(), self.height())
     }
-}
 
-impl Error for ParseGridError {
-    #[allow(deprecated)]
-    fn description(&self) -> &str {
-        "failed to parse cmd"
+    fn colour(&self, pos: Position) -> Rgb {
+        Rgb(10, 40, 10).blend(Rgb(140, 230, 100), f32::from(self[pos]) / 9.0)
     }
 }
 
 impl FromStr for Grid {
-    type Err = ParseGridError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let result = s
-            .split('\n')
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .map(|line| {
-                line.chars()
-                    .map(|c| c.to_digit(10).unwrap() as u8)
-                    .collect::<Vec<u8>>()
-            })
-            .collect::<Vec<Vec<u8>>>();
-        Ok(Self(result))
+        grid::Grid::parse(Day08::DAY, s, "a tree height", |_, c| {
+            Some(c.to_digit(10)? as u8)
+        })
+        .map(Self)
     }
 }
 
+/// The forest of `input`.
+pub fn picture(input: &str) -> impl Render {
+    parse::or_panic(input.parse::<Grid>())
+}
+
 fn first(input: &str) -> usize {
-    let grid: Grid = input.parse().unwrap();
+    let grid: Grid = parsing(|| parse::or_panic(input.parse()));
     grid.all_visible_count()
 }
 
 fn second(input: &str) -> usize {
-    let grid: Grid = input.parse().unwrap();
+    let grid: Grid = parsing(|| parse::or_panic(input.parse()));
     grid.highest_scenic_score()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     static INPUT: &str = "
         30373
@@ -233,4 +146,22 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 8);
     }
+
+    #[test]
+    fn test_render() {
+        let grid: Grid = INPUT.parse().unwrap();
+        let image = grid.render();
+        assert_eq!((image.width(), image.height()), (5, 5));
+        // a 0 and a 9
+        assert_eq!(image[Position::new(1, 0)], Rgb(10, 40, 10));
+        assert_eq!(image[Position::new(4, 3)], Rgb(140, 230, 100));
+        assert!(image[Position::new(0, 0)].1 < image[Position::new(3, 0)].1);
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day08.run_part1(&input), Answer::Integer(21));
+        assert_eq!(Day08.run_part2(&input), Answer::Integer(8));
+    }
 }
@@ -1,64 +1,45 @@
-use std::{collections::HashSet, error::Error, fmt, str::FromStr};
-
-pub fn run() {
-    // let input = r#"
-    //     R 5
-    //     U 8
-    //     L 8
-    //     D 3
-    //     R 17
-    //     D 10
-    //     L 25
-    //     U 20
-    // "#;
-    let input = include_str!("../input/day09/input");
-    dbg!(first(input));
-    dbg!(second(input));
-}
+use std::{collections::HashSet, fmt, str::FromStr};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Position {
-    x: isize,
-    y: isize,
-}
+use crate::{
+    Answer, Solver,
+    animate::{Observer, Recorder},
+    bench::parsing,
+    geometry::Point,
+    grid::Grid,
+    parse::{self, ParseError},
+    render::{Render, Rgb},
+};
 
-impl Position {
-    pub fn new(x: isize, y: isize) -> Self {
-        Self { x, y }
-    }
+pub struct Day09;
 
-    pub fn is_touching(&self, other: &Self) -> bool {
-        (self.x - other.x).abs() <= 1 && (self.y - other.y).abs() <= 1
-    }
+impl Solver for Day09 {
+    const DAY: u32 = 9;
 
-    pub fn move_towards(&self, other: &Self) -> Option<Self> {
-        if self.is_touching(other) {
-            return None;
-        }
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
 
-        Some(Position {
-            x: self.x + (other.x - self.x).signum(),
-            y: self.y + (other.y - self.y).signum(),
-        })
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
     }
 }
 
-impl From<(isize, isize)> for Position {
-    fn from(value: (isize, isize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
+/// Knot position, `y` grows upwards.
+type Position = Point<isize>;
+
+fn is_touching(knot: Position, head: Position) -> bool {
+    knot.chebyshev(head) <= 1
 }
 
-impl From<&(isize, isize)> for Position {
-    fn from(value: &(isize, isize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
+/// Where a knot following `head` moves to, `None` while they still touch.
+fn move_towards(knot: Position, head: Position) -> Option<Position> {
+    if is_touching(knot, head) {
+        return None;
     }
+    Some(Position::new(
+        knot.x + (head.x - knot.x).signum(),
+        knot.y + (head.y - knot.y).signum(),
+    ))
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -77,37 +58,30 @@ pub enum Motion {
     Right(usize),
 }
 
-/// An error returned when parsing a `bool` using [`from_str`] fails
-///
-/// [`from_str`]: super::FromStr::from_str
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ParseMotionError;
-
-impl fmt::Display for ParseMotionError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        "provided string was not a motion".fmt(f)
-    }
-}
-
-impl Error for ParseMotionError {
-    #[allow(deprecated)]
-    fn description(&self) -> &str {
-        "failed to parse motion"
-    }
-}
-
 impl FromStr for Motion {
-    type Err = ParseMotionError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let splited: Vec<&str> = s.split_ascii_whitespace().collect();
-        match splited[0] {
-            "U" => Ok(Self::Up(splited[1].parse().unwrap())),
-            "D" => Ok(Self::Down(splited[1].parse().unwrap())),
-            "L" => Ok(Self::Left(splited[1].parse().unwrap())),
-            "R" => Ok(Self::Right(splited[1].parse().unwrap())),
-            _ => Err(ParseMotionError),
+        let mut words = s.split_ascii_whitespace();
+        let direction = words
+            .next()
+            .ok_or_else(|| ParseError::end_of(Day09::DAY, s, "a direction"))?;
+        let steps = words
+            .next()
+            .ok_or_else(|| ParseError::end_of(Day09::DAY, s, "a step count"))?;
+        let steps =
+            parse::token(Day09::DAY, steps, "a step count").map_err(|e| e.rebase(s, steps))?;
+        match direction {
+            "U" => Ok(Self::Up(steps)),
+            "D" => Ok(Self::Down(steps)),
+            "L" => Ok(Self::Left(steps)),
+            "R" => Ok(Self::Right(steps)),
+            _ => Err(ParseError::within(
+                Day09::DAY,
+                s,
+                direction,
+                "`U`, `D`, `L` or `R`",
+            )),
         }
     }
 }
@@ -162,53 +136,40 @@ impl Rope {
     }
 
     fn move_motion(&mut self, motion: &Motion) {
-        match motion {
-            Motion::Up(n) => {
-                for _ in 0..*n {
-                    self.move_once(&Direction::Up);
-                }
-            }
-            Motion::Down(n) => {
-                for _ in 0..*n {
-                    self.move_once(&Direction::Down);
-                }
-            }
-            Motion::Left(n) => {
-                for _ in 0..*n {
-                    self.move_once(&Direction::Left);
-                }
-            }
-            Motion::Right(n) => {
-                for _ in 0..*n {
-                    self.move_once(&Direction::Right);
-                }
-            }
+        self.move_motion_observed(motion, &mut ());
+    }
+
+    /// [`Rope::move_motion`], showing the rope to `observer` after every step.
+    fn move_motion_observed(&mut self, motion: &Motion, observer: &mut impl Observer<Self>) {
+        let (direction, n) = match motion {
+            Motion::Up(n) => (Direction::Up, n),
+            Motion::Down(n) => (Direction::Down, n),
+            Motion::Left(n) => (Direction::Left, n),
+            Motion::Right(n) => (Direction::Right, n),
+        };
+        for _ in 0..*n {
+            self.move_once(&direction);
+            observer.observe(self);
         }
     }
 
     fn move_once(&mut self, direct: &Direction) {
-        match direct {
-            Direction::Up => {
-                self.knots[0] = Position::new(self.knots[0].x, self.knots[0].y + 1);
-                self.up = self.up.max(self.knots[0].y);
-            }
-            Direction::Down => {
-                self.knots[0] = Position::new(self.knots[0].x, self.knots[0].y - 1);
-                self.down = self.down.min(self.knots[0].y);
-            }
-            Direction::Left => {
-                self.knots[0] = Position::new(self.knots[0].x - 1, self.knots[0].y);
-                self.left = self.left.min(self.knots[0].x);
-            }
-            Direction::Right => {
-                self.knots[0] = Position::new(self.knots[0].x + 1, self.knots[0].y);
-                self.right = self.right.max(self.knots[0].x);
-            }
+        let step = match direct {
+            Direction::Up => Position::new(0, 1),
+            Direction::Down => Position::new(0, -1),
+            Direction::Left => Position::new(-1, 0),
+            Direction::Right => Position::new(1, 0),
         };
+        let head = self.knots[0] + step;
+        self.knots[0] = head;
+        self.up = self.up.max(head.y);
+        self.down = self.down.min(head.y);
+        self.left = self.left.min(head.x);
+        self.right = self.right.max(head.x);
         let before = self.knots.clone();
         let mut prev = before[0];
         for (idx, knot) in before.iter().enumerate().skip(1) {
-            if let Some(p) = knot.move_towards(&prev) {
+            if let Some(p) = move_towards(*knot, prev) {
                 self.knots[idx] = p;
                 if idx == before.len() - 1 {
                     self.tail_path.push(p);
@@ -218,21 +179,85 @@ impl Rope {
                 break;
             }
         }
-        // println!("{self}");
+    }
+
+    /// Colour of knot `idx` in [`Render`], from the head to the tail.
+    fn knot_colour(&self, idx: usize) -> Rgb {
+        HEAD.blend(TAIL, idx as f32 / (self.knots.len() - 1) as f32)
+    }
+}
+
+/// Palette of [`Render`].
+const BACKGROUND: Rgb = Rgb(20, 20, 30);
+const VISITED: Rgb = Rgb(70, 90, 120);
+const START: Rgb = Rgb(60, 200, 90);
+const HEAD: Rgb = Rgb(230, 50, 50);
+const TAIL: Rgb = Rgb(250, 200, 60);
+
+/// The box the head went through, `y` pointing up as in [`fmt::Display`].
+impl Render for Rope {
+    fn size(&self) -> (usize, usize) {
+        (
+            self.left.abs_diff(self.right) + 1,
+            self.down.abs_diff(self.up) + 1,
+        )
+    }
+
+    fn colour(&self, pos: Point<usize>) -> Rgb {
+        let pos = Position::new(self.left + pos.x as isize, self.up - pos.y as isize);
+        // the knot nearest the head shows when several overlap
+        if let Some(idx) = self.knots.iter().position(|k| *k == pos) {
+            self.knot_colour(idx)
+        } else if pos == Position::default() {
+            START
+        } else if self.tail_path.contains(&pos) {
+            VISITED
+        } else {
+            BACKGROUND
+        }
+    }
+
+    fn origin(&self) -> Point<isize> {
+        Point::new(self.left, -self.up)
+    }
+
+    fn background(&self) -> Rgb {
+        BACKGROUND
+    }
+
+    /// Same picture as [`Render::colour`] gives, painting the tail path once instead of
+    /// searching it for every cell.
+    fn render(&self) -> Grid<Rgb> {
+        let (width, height) = self.size();
+        let mut image = Grid::new(width, height, BACKGROUND);
+        // every knot stays in the box the head went through
+        let cell = |p: &Position| Point::new(p.x.abs_diff(self.left), p.y.abs_diff(self.up));
+        for pos in &self.tail_path {
+            image[cell(pos)] = VISITED;
+        }
+        image[cell(&Position::default())] = START;
+        for (idx, knot) in self.knots.iter().enumerate().rev() {
+            image[cell(knot)] = self.knot_colour(idx);
+        }
+        image
     }
 }
 
 fn to_motions(input: &str) -> Vec<Motion> {
-    input
-        .split('\n')
-        .map(|i| i.trim())
-        .filter(|i| !i.is_empty())
-        .map(|i| i.parse().unwrap())
-        .collect()
+    parse::or_panic(parse::each_line(input, str::parse))
+}
+
+/// Record the rope of part 2 going through the motions of `input`, ending on its last state.
+pub fn animate(input: &str, recorder: &mut Recorder) {
+    let mut rope = Rope::with_knots(9);
+    for motion in to_motions(input) {
+        rope.move_motion_observed(&motion, recorder);
+    }
+    recorder.capture(&rope);
 }
 
 fn first(input: &str) -> usize {
-    let motions: Vec<Motion> = to_motions(input);
+    let motions: Vec<Motion> = parsing(|| to_motions(input));
     let mut rope = Rope::with_knots(1);
     for motion in motions {
         rope.move_motion(&motion);
@@ -244,7 +269,7 @@ fn first(input: &str) -> usize {
 }
 
 fn second(input: &str) -> usize {
-    let motions: Vec<Motion> = to_motions(input);
+    let motions: Vec<Motion> = parsing(|| to_motions(input));
     let mut rope = Rope::with_knots(9);
     for motion in motions {
         rope.move_motion(&motion);
@@ -258,59 +283,90 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
+
+    static INPUT: &str = "
+            R 4
+            U 4
+            L 3
+            D 1
+            R 4
+            D 1
+            L 5
+            R 2
+        ";
+
+    static LARGER_INPUT: &str = "
+            R 5
+            U 8
+            L 8
+            D 3
+            R 17
+            D 10
+            L 25
+            U 20
+        ";
 
     #[test]
     fn test_is_touching() {
         let head = Position::new(3, 3);
         let tail = Position::new(2, 4);
-        assert!(tail.is_touching(&head));
+        assert!(is_touching(tail, head));
     }
 
     #[test]
     fn test_move_towards() {
         let head = Position::new(3, 3);
         let tail = Position::new(2, 4);
-        let after = tail.move_towards(&head);
+        let after = move_towards(tail, head);
         assert!(after.is_none());
 
         let head = Position::new(4, 3);
         let tail = Position::new(2, 4);
-        let after = tail.move_towards(&head);
+        let after = move_towards(tail, head);
         assert_eq!(after, Some(Position::new(3, 3)));
 
         let head = Position::new(3, 2);
         let tail = Position::new(4, 3);
-        let after = tail.move_towards(&head);
+        let after = move_towards(tail, head);
         assert!(after.is_none());
     }
 
     #[test]
     fn test_first() {
-        let input = "
-            R 4
-            U 4
-            L 3
-            D 1
-            R 4
-            D 1
-            L 5
-            R 2
-        ";
-        assert_eq!(first(input), 13);
+        assert_eq!(first(INPUT), 13);
     }
 
     #[test]
     fn test_second() {
-        let input = r#"
-            R 5
-            U 8
-            L 8
-            D 3
-            R 17
-            D 10
-            L 25
-            U 20
-        "#;
-        assert_eq!(second(input), 36);
+        assert_eq!(second(LARGER_INPUT), 36);
+    }
+
+    #[test]
+    fn test_animate() {
+        let mut rope = Rope::with_knots(9);
+        let mut recorder = Recorder::default().with_margin(0);
+        for motion in to_motions(LARGER_INPUT) {
+            rope.move_motion_observed(&motion, &mut recorder);
+        }
+        assert_eq!(recorder.len(), 5 + 8 + 8 + 3 + 17 + 10 + 25 + 20);
+        // the head went from x = -11 to 14 and from y = -5 to 15
+        let frames = recorder.frames();
+        assert!(frames.iter().all(|f| (f.width(), f.height()) == (26, 21)));
+        let last = frames.last().unwrap();
+        assert_eq!(last[Point::new(0, 0)], HEAD);
+        assert_eq!(last[Point::new(11, 15)], START);
+        assert_eq!(frames[0][Point::new(12, 15)], HEAD);
+        assert!(last.iter().all(|(pos, c)| *c == rope.colour(pos)));
+
+        let mut steps = 0;
+        rope.move_motion_observed(&Motion::Down(3), &mut |_: &Rope| steps += 1);
+        assert_eq!(steps, 3);
+    }
+
+    #[test]
+    fn test_crlf() {
+        assert_eq!(Day09.run_part1(&crlf(INPUT)), Answer::Integer(13));
+        assert_eq!(Day09.run_part2(&crlf(LARGER_INPUT)), Answer::Integer(36));
     }
 }
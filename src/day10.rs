@@ -1,4 +1,10 @@
-use std::{error::Error, fmt, str::FromStr};
+use std::{fmt, str::FromStr};
+
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    parse::{self, ParseError},
+};
 
 #[allow(dead_code)]
 static INPUT: &str = r#"
@@ -150,10 +156,18 @@ static INPUT: &str = r#"
     noop
 "#;
 
-pub fn run() {
-    let input = include_str!("../input/day10/input");
-    dbg!(first(input));
-    print!("{}", second(input));
+pub struct Day10;
+
+impl Solver for Day10 {
+    const DAY: u32 = 10;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        Answer::Grid(second(input))
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -190,35 +204,23 @@ impl Instruction {
     }
 }
 
-/// An error returned when parsing a `bool` using [`from_str`] fails
-///
-/// [`from_str`]: super::FromStr::from_str
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ParseInstructionError;
-
-impl fmt::Display for ParseInstructionError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        "provided string was not a instruction".fmt(f)
-    }
-}
-
-impl Error for ParseInstructionError {
-    #[allow(deprecated)]
-    fn description(&self) -> &str {
-        "failed to parse motion"
-    }
-}
-
 impl FromStr for Instruction {
-    type Err = ParseInstructionError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let splited: Vec<&str> = s.split_ascii_whitespace().collect();
-        match splited[0] {
-            "noop" => Ok(Self::Noop),
-            "addx" => Ok(Self::AddX(splited[1].parse().unwrap())),
-            _ => Err(ParseInstructionError),
+        let mut words = s.split_ascii_whitespace();
+        match words.next() {
+            Some("noop") => Ok(Self::Noop),
+            Some("addx") => {
+                let value = words
+                    .next()
+                    .ok_or_else(|| ParseError::end_of(Day10::DAY, s, "a value"))?;
+                parse::token(Day10::DAY, value, "a signed value")
+                    .map(Self::AddX)
+                    .map_err(|e| e.rebase(s, value))
+            }
+            Some(other) => Err(ParseError::within(Day10::DAY, s, other, "`noop` or `addx`")),
+            None => Err(ParseError::end_of(Day10::DAY, s, "`noop` or `addx`")),
         }
     }
 }
@@ -339,22 +341,17 @@ impl CPU {
 }
 
 fn to_instuctions(input: &str) -> Vec<Instruction> {
-    input
-        .split('\n')
-        .map(|i| i.trim())
-        .filter(|i| !i.is_empty())
-        .map(|i| i.parse().unwrap())
-        .collect()
+    parse::or_panic(parse::each_line(input, str::parse))
 }
 
 fn first(input: &str) -> isize {
-    let instructions = to_instuctions(input);
+    let instructions = parsing(|| to_instuctions(input));
     let mut cpu = CPU::with_instructions(instructions);
     cpu.get_all_signal_strength(20, 40, 6).into_iter().sum()
 }
 
 fn second(input: &str) -> String {
-    let instructions = to_instuctions(input);
+    let instructions = parsing(|| to_instuctions(input));
     let mut cpu = CPU::with_instructions(instructions);
     let mut crt = CRT::new(40, 6);
     for i in 0..crt.pixels.len() {
@@ -371,6 +368,7 @@ fn second(input: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     #[test]
     fn test_cycle_signal_strength() {
@@ -408,4 +406,11 @@ mod tests {
 "#;
         assert_eq!(second(INPUT).trim(), output.trim().to_string());
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day10.run_part1(&input), Answer::Integer(13140));
+        assert_eq!(Day10.run_part2(&input), second(INPUT).as_str());
+    }
 }
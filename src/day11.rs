@@ -1,8 +1,14 @@
-use std::{
-    collections::VecDeque,
-    error::Error,
-    fmt::{self, Debug},
-    str::FromStr,
+use std::{collections::VecDeque, str::FromStr};
+
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    combinator::{
+        self, Parser, block, blocks, end_of_line, field, int, label, literal, map, or, preceded,
+        separated, spaces, terminated,
+    },
+    cycle,
+    parse::{self, ParseError},
 };
 
 #[allow(dead_code)]
@@ -36,16 +42,23 @@ Monkey 3:
     If false: throw to monkey 1
 "#;
 
-pub fn run() {
-    let input = include_str!("../input/day11/input");
-    dbg!(first(input));
-    dbg!(second(input));
+pub struct Day11;
+
+impl Solver for Day11 {
+    const DAY: u32 = 11;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
+    }
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Machine {
     monkeys: Vec<Monkey>,
-    round: usize,
     inspects: Vec<usize>,
     part: Part,
 }
@@ -92,94 +105,60 @@ impl Operation {
     }
 }
 
-impl FromStr for Operation {
-    type Err = ParseMonkeyError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let operation_str_vec: Vec<&str> = s
-            .split('=')
-            .nth(1)
-            .unwrap()
-            .trim()
-            .split_ascii_whitespace()
-            .collect();
-        let operation = match (operation_str_vec[1], operation_str_vec[2]) {
-            ("+", "old") => Operation {
-                op: OP::AddAssign,
-                operand: None,
-            },
-            ("*", "old") => Operation {
-                op: OP::MulAssign,
-                operand: None,
-            },
-            ("+", n) => Operation {
-                op: OP::Add,
-                operand: Some(n.parse().unwrap()),
-            },
-            ("*", n) => Operation {
-                op: OP::Mul,
-                operand: Some(n.parse().unwrap()),
-            },
-            _ => return Err(ParseMonkeyError),
-        };
-        Ok(operation)
-    }
-}
-/// An error returned when parsing a `bool` using [`from_str`] fails
-///
-/// [`from_str`]: super::FromStr::from_str
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ParseMonkeyError;
-
-impl fmt::Display for ParseMonkeyError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "provided string was not a monkey")
-    }
-}
-
-impl Error for ParseMonkeyError {
-    #[allow(deprecated)]
-    fn description(&self) -> &str {
-        "failed to parse monkey"
-    }
+/// "new = old * 19"
+fn operation<'a>() -> impl Parser<'a, Operation> {
+    let operand = or(map(literal("old"), |_| None), map(int(), Some));
+    map(
+        (
+            literal("new = old "),
+            or(literal("+"), literal("*")),
+            preceded(literal(" "), operand),
+        ),
+        |(_, op, operand)| {
+            let op = match (op, operand) {
+                ("+", None) => OP::AddAssign,
+                ("+", Some(_)) => OP::Add,
+                (_, None) => OP::MulAssign,
+                (_, Some(_)) => OP::Mul,
+            };
+            Operation { op, operand }
+        },
+    )
 }
 
-fn last_num(s: &str) -> usize {
-    s.split_ascii_whitespace()
-        .last()
-        .unwrap()
-        .parse::<usize>()
-        .unwrap()
+fn monkey<'a>() -> impl Parser<'a, Monkey> {
+    let header = terminated(
+        preceded((spaces(), literal("Monkey ")), int::<usize>()),
+        (literal(":"), end_of_line()),
+    );
+    let items = separated(label(int(), "a worry level"), literal(", "));
+    let throw_to = || preceded(literal("throw to monkey "), int());
+    map(
+        (
+            header,
+            field("Starting items", items),
+            field("Operation", operation()),
+            (
+                field("Test", preceded(literal("divisible by "), int())),
+                field("If true", throw_to()),
+                field("If false", throw_to()),
+            ),
+        ),
+        |(_, items, operation, (test_div, true_to, false_to))| Monkey {
+            items: items.into(),
+            operation,
+            test_div,
+            true_to,
+            false_to,
+        },
+    )
 }
 
 impl FromStr for Monkey {
-    type Err = ParseMonkeyError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lines: Vec<&str> = s
-            .lines()
-            .skip(1)
-            .map(|i| i.trim())
-            .filter(|i| !i.is_empty())
-            .collect();
-        let item_num_str = lines[0].split(':').nth(1).unwrap();
-        let items = item_num_str
-            .split(',')
-            .map(|n| n.trim().parse::<usize>().unwrap())
-            .collect::<VecDeque<usize>>();
-
-        let operation = lines[1].parse()?;
-        let test_div = last_num(lines[2]);
-        let true_to = last_num(lines[3]);
-        let false_to = last_num(lines[4]);
-        Ok(Self {
-            items,
-            operation,
-            test_div,
-            true_to,
-            false_to,
-        })
+        combinator::run(Day11::DAY, s, block(monkey()))
     }
 }
 
@@ -207,35 +186,89 @@ impl Machine {
         l % div_by
     }
 
+    fn lower_worry_levels(&self, l: usize) -> usize {
+        match &self.part {
+            Part::One => self.part_one_lower_worry_levels(l),
+            Part::Two => self.part_two_lower_worry_levels(l),
+        }
+    }
+
+    /// Inspect `worry_level` at monkey `i` and return the monkey it is thrown to with its new
+    /// worry level.
+    fn inspect(&self, i: usize, worry_level: usize) -> (usize, usize) {
+        let monkey = &self.monkeys[i];
+        let new_worry_level = self.lower_worry_levels(monkey.operation.to_func()(worry_level));
+        if new_worry_level.is_multiple_of(monkey.test_div) {
+            (monkey.true_to, new_worry_level)
+        } else {
+            (monkey.false_to, new_worry_level)
+        }
+    }
+
+    /// Plain round by round simulation, to check the skipping ahead against.
+    #[cfg(test)]
     fn run_once(&mut self) {
         for i in 0..self.monkeys.len() {
             while let Some(worry_level) = self.monkeys[i].items.pop_front() {
                 self.inspects[i] += 1;
-                let true_to = self.monkeys[i].true_to;
-                let false_to = self.monkeys[i].false_to;
-                let mut new_worry_level = self.monkeys[i].operation.to_func()(worry_level);
-                new_worry_level = match &self.part {
-                    Part::One => self.part_one_lower_worry_levels(new_worry_level),
-                    Part::Two => self.part_two_lower_worry_levels(new_worry_level),
-                };
-                if new_worry_level % self.monkeys[i].test_div == 0 {
-                    self.monkeys[true_to].items.push_back(new_worry_level);
-                } else {
-                    self.monkeys[false_to].items.push_back(new_worry_level);
-                }
+                let (to, new_worry_level) = self.inspect(i, worry_level);
+                self.monkeys[to].items.push_back(new_worry_level);
             }
         }
     }
 
-    fn run_until_round(&mut self, round: usize) {
-        for _ in (self.round + 1)..=round {
+    #[cfg(test)]
+    fn run_rounds(&mut self, rounds: usize) {
+        for _ in 0..rounds {
             self.run_once();
         }
     }
 
+    /// Inspections done by each monkey over `rounds` rounds on the item starting with
+    /// `worry_level` at monkey `holder`.
+    ///
+    /// Items never meet, so each one is followed on its own until it is back at the same
+    /// monkey with the same worry level, the remaining rounds only repeat that.
+    fn item_inspections(&self, holder: usize, worry_level: usize, rounds: usize) -> Vec<usize> {
+        let mut counts = vec![0; self.monkeys.len()];
+        let mut history = vec![counts.clone()];
+        let mut item = (holder, worry_level);
+        let cycle = cycle::find_cycle(
+            &mut item,
+            |item| *item,
+            |(holder, worry_level)| {
+                // a round ends when the item is thrown back to a monkey that already had its turn
+                loop {
+                    counts[*holder] += 1;
+                    let (to, new_worry_level) = self.inspect(*holder, *worry_level);
+                    let next_round = to < *holder;
+                    (*holder, *worry_level) = (to, new_worry_level);
+                    if next_round {
+                        break;
+                    }
+                }
+                history.push(counts.clone());
+            },
+            rounds,
+        );
+        match cycle {
+            Some(cycle) => (0..self.monkeys.len())
+                .map(|i| cycle.extrapolate(rounds, |r| history[r][i] as i64) as usize)
+                .collect(),
+            None => history.swap_remove(rounds),
+        }
+    }
+
     fn monkey_business(&mut self, round: usize) -> usize {
-        self.run_until_round(round);
         let mut inspects = self.inspects.clone();
+        for (holder, monkey) in self.monkeys.iter().enumerate() {
+            for &worry_level in &monkey.items {
+                let item = self.item_inspections(holder, worry_level, round);
+                for (total, count) in inspects.iter_mut().zip(item) {
+                    *total += count;
+                }
+            }
+        }
         inspects.sort();
         inspects.reverse();
         inspects[0] * inspects[1]
@@ -243,22 +276,17 @@ impl Machine {
 }
 
 fn to_monkeys(input: &str) -> Vec<Monkey> {
-    input
-        .split("\n\n")
-        .map(|i| i.trim())
-        .filter(|i| !i.is_empty())
-        .map(|i| i.parse().unwrap())
-        .collect()
+    parse::or_panic(combinator::run(Day11::DAY, input, blocks(monkey())))
 }
 
 fn first(input: &str) -> usize {
-    let monkeys = to_monkeys(input);
+    let monkeys = parsing(|| to_monkeys(input));
     let mut m = Machine::default().with_monkeys(monkeys);
     m.monkey_business(20)
 }
 
 fn second(input: &str) -> usize {
-    let monkeys = to_monkeys(input);
+    let monkeys = parsing(|| to_monkeys(input));
     let mut m = Machine::default()
         .with_monkeys(monkeys)
         .with_part(Part::Two);
@@ -268,6 +296,7 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     #[test]
     fn test_first() {
@@ -278,4 +307,50 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 2713310158);
     }
+
+    #[test]
+    fn test_item_inspections() {
+        // part one worry levels overflow in the long run
+        for (part, rounds) in [(Part::One, 20), (Part::Two, 1000)] {
+            let machine = Machine::default()
+                .with_monkeys(to_monkeys(INPUT))
+                .with_part(part);
+            let mut simulated = machine.clone();
+            simulated.run_rounds(rounds);
+            let mut inspects = vec![0; 4];
+            for (holder, monkey) in machine.monkeys.iter().enumerate() {
+                for &worry_level in &monkey.items {
+                    let item = machine.item_inspections(holder, worry_level, rounds);
+                    inspects.iter_mut().zip(item).for_each(|(t, c)| *t += c);
+                }
+            }
+            assert_eq!(inspects, simulated.inspects);
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let monkey = "Monkey 2:\n  Starting items: 79\n  Operation: new = old ^ old\n";
+        let error = monkey.parse::<Monkey>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 11, line 3, column 24: expected `+` or `*`, found `^`"
+        );
+        let error = "Monkey 0:\n  Starting items: 79, x"
+            .parse::<Monkey>()
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(23)));
+        assert_eq!(error.expected, "a worry level");
+        let error = "Monkey 0:\n  Starting items: 79\n"
+            .parse::<Monkey>()
+            .unwrap_err();
+        assert_eq!(error.expected, "`Operation`");
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day11.run_part1(&input), Answer::Integer(10605));
+        assert_eq!(Day11.run_part2(&input), Answer::Integer(2713310158));
+    }
 }
@@ -1,12 +1,20 @@
 use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashMap, HashSet},
-    error::Error,
     fmt::{self, Debug},
+    iter,
     ops::Deref,
     str::FromStr,
 };
 
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    geometry::Point,
+    grid::Grid,
+    parse::{self, ParseError},
+    render::{Render, Rgb},
+    search,
+};
+
 #[allow(dead_code)]
 static INPUT: &str = r#"
 Sabqponm
@@ -16,242 +24,95 @@ acctuvwj
 abdefghi
 "#;
 
-pub fn run() {
-    let input = include_str!("../input/day12/input");
-    // let hm: HeightMap = input.parse().unwrap();
-    dbg!(first(input));
-    dbg!(second(input));
-
-    let hm: HeightMap = INPUT.parse().unwrap();
-    // dbg!(&hm);
-    println!("{}", &hm);
-
-    let path = hm.shortest_path();
-    dbg!(&path.inner.len());
-    hm.print_path(&path);
-    // dbg!(&path.inner.len());
-}
-
-#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
-pub struct Position {
-    x: usize,
-    y: usize,
-}
-
-impl Ord for Position {
-    // Reading order: Y then X
-    fn cmp(&self, other: &Self) -> Ordering {
-        (self.y, self.x).cmp(&(other.y, other.x))
-    }
-}
+pub struct Day12;
 
-impl PartialOrd for Position {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
+impl Solver for Day12 {
+    const DAY: u32 = 12;
 
-impl Position {
-    pub fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
     }
 
-    #[inline]
-    pub fn distance_squared(&self, other: Self) -> usize {
-        let dx = if self.x > other.x {
-            self.x - other.x
-        } else {
-            other.x - self.x
-        };
-        let dy = if self.y > other.y {
-            self.y - other.y
-        } else {
-            other.y - self.y
-        };
-        dx * dx + dy * dy
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
     }
 }
 
-impl From<(usize, usize)> for Position {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
-}
-
-impl From<&(usize, usize)> for Position {
-    fn from(value: &(usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+type Position = Point<usize>;
 
 #[derive(Debug, Clone)]
 pub struct HeightMap {
     start: Position,
-    end: Option<Position>,
-    grid: Vec<Vec<u8>>,
+    end: Position,
+    grid: Grid<u8>,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct ClimbPath {
+    start: Position,
     inner: Vec<Position>,
-    directions: Vec<Direction>,
-}
-
-// Generic struct used to select an item based on a minimum score.
-// Use with std::collections::BinaryHeap for problems requiring Djikstra's
-// Algorithm or A*
-
-#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
-pub struct ScoredItem<N, T> {
-    pub cost: N,
-    pub item: T,
-}
-
-impl<N: Ord + PartialOrd, T: Ord + PartialOrd> Ord for ScoredItem<N, T> {
-    fn cmp(&self, other: &ScoredItem<N, T>) -> Ordering {
-        (&other.cost, &other.item).cmp(&(&self.cost, &self.item))
-    }
-}
-
-impl<N: Ord + PartialOrd, T: Ord + PartialOrd> PartialOrd for ScoredItem<N, T> {
-    fn partial_cmp(&self, other: &ScoredItem<N, T>) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
 }
 
 impl HeightMap {
     #[inline]
-    fn row_len(&self) -> usize {
-        self.grid[0].len()
-    }
-
-    #[inline]
-    fn col_len(&self) -> usize {
-        self.grid.len()
+    fn can_climb(&self, cur: &Position, next: &Position) -> bool {
+        // at most one step up, any step down
+        self.grid[*next] <= self.grid[*cur] + 1
     }
 
-    #[inline]
-    fn pos_item(&self, pos: impl Into<Position>) -> u8 {
-        let p = pos.into();
-        self.grid[p.y][p.x]
+    fn climbable(&self, pos: &Position) -> impl Iterator<Item = Position> + '_ {
+        let pos = *pos;
+        self.grid
+            .neighbours4(pos)
+            .filter(move |next| self.can_climb(&pos, next))
     }
 
-    #[inline]
-    fn can_climb(&self, cur: &Position, next: &Position) -> bool {
-        (self.pos_item(*next) == self.pos_item(*cur))
-            || (self.pos_item(*next) == self.pos_item(*cur) + 1)
+    /// Fewest steps from any of `starts` to the end.
+    fn shortest_path(&self, starts: impl IntoIterator<Item = Position>) -> ClimbPath {
+        let Some(path) = search::bfs(starts, |p| self.climbable(p), |p| *p == self.end) else {
+            return ClimbPath::default();
+        };
+        ClimbPath {
+            start: *path.start(),
+            inner: path.nodes[1..].to_vec(),
+        }
     }
 
-    fn neightbors(&self, pos: &Position) -> Vec<(Position, Direction)> {
-        let mut result = vec![];
-        if pos.x > 0 {
-            let left = Position::new(pos.x - 1, pos.y);
-            result.push((left, Direction::Left));
-        }
-        if pos.x < self.row_len() - 1 {
-            let right = Position::new(pos.x + 1, pos.y);
-            result.push((right, Direction::Right));
-        }
-        // upside down
-        if pos.y > 0 {
-            let up = Position::new(pos.x, pos.y - 1);
-            result.push((up, Direction::Up));
+    /// Picture of the map with `path` drawn over it.
+    fn render_path(&self, path: &ClimbPath) -> Grid<Rgb> {
+        let mut image = self.render();
+        for &pos in iter::once(&path.start).chain(&path.inner) {
+            image[pos] = PATH;
         }
-        if pos.y < self.col_len() - 1 {
-            let down = Position::new(pos.x, pos.y + 1);
-            result.push((down, Direction::Down));
-        }
-        result
+        image
     }
+}
 
-    fn shortest_path(&self) -> ClimbPath {
-        let mut queue = BinaryHeap::from([ScoredItem {
-            cost: 0,
-            item: self.start,
-        }]);
-        let mut visited = HashSet::new();
-        let mut came_from: HashMap<Position, (Position, Direction)> = HashMap::new();
-        let mut climb_path = ClimbPath::default();
-
-        while !queue.is_empty() {
-            let step = queue.pop().unwrap();
-            if visited.contains(&step.item) {
-                continue;
-            }
-            if self.end == Some(step.item)
-                || (self.end.is_none() && self.pos_item(step.item) == my_char_to_u8('z'))
-            {
-                let mut curr = step.item;
-                while curr != self.start {
-                    let f = came_from.get(&curr).unwrap().to_owned();
-                    climb_path.inner.push(curr);
-                    climb_path.directions.push(f.1);
-                    curr = f.0;
-                }
-                climb_path.inner.reverse();
-                climb_path.directions.reverse();
-                return climb_path;
-            }
+const LOWEST: Rgb = Rgb(20, 70, 30);
+const HIGHEST: Rgb = Rgb(240, 240, 230);
+const START: Rgb = Rgb(60, 140, 255);
+const END: Rgb = Rgb(230, 40, 40);
+const PATH: Rgb = Rgb(255, 200, 0);
 
-            visited.insert(step.item);
-            for n in self.neightbors(&step.item) {
-                if self.can_climb(&step.item, &n.0) {
-                    queue.push(ScoredItem {
-                        cost: step.cost + 1,
-                        item: n.0,
-                    });
-                    if !visited.contains(&n.0) {
-                        came_from.insert(n.0, (step.item, n.1));
-                    }
-                }
-            }
-        }
-        climb_path
+/// Heights from dark green up to white, with the start and the end standing out.
+impl Render for HeightMap {
+    fn size(&self) -> (usize, usize) {
+        (self.grid.width(), self.grid.height())
     }
 
-    fn print_path(&self, path: &ClimbPath) {
-        for col in 0..self.col_len() {
-            for row in 0..self.row_len() {
-                let pos = Position::new(row, col);
-                if let Some(idx) = [self.start]
-                    .iter()
-                    .chain(path.inner.iter())
-                    .take(path.inner.len())
-                    .position(|i| i == &pos)
-                {
-                    match path.directions[idx] {
-                        Direction::Up => print!("^"),
-                        Direction::Down => print!("v"),
-                        Direction::Left => print!("<"),
-                        Direction::Right => print!(">"),
-                    }
-                } else if Some(pos) == self.end {
-                    print!("E");
-                } else {
-                    print!(".");
-                }
-            }
-            println!();
+    fn colour(&self, pos: Position) -> Rgb {
+        if pos == self.start {
+            START
+        } else if pos == self.end {
+            END
+        } else {
+            LOWEST.blend(HIGHEST, f32::from(self.grid[pos]) / 25.0)
         }
     }
 }
 
 impl Deref for HeightMap {
-    type Target = Vec<Vec<u8>>;
+    type Target = Grid<u8>;
 
     fn deref(&self) -> &Self::Target {
         &self.grid
@@ -261,20 +122,10 @@ impl Deref for HeightMap {
 impl fmt::Display for HeightMap {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{:?}, {:?}", self.start, self.end)?;
-        for col in 0..self.col_len() {
-            for row in 0..self.row_len() {
-                let pos = Position::new(row, col);
-                if pos == self.start {
-                    write!(f, "S")?;
-                } else if Some(pos) == self.end {
-                    write!(f, "E")?;
-                } else {
-                    write!(f, "{}", my_u8_to_char(self.pos_item(pos)))?;
-                }
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        let mut view = self.grid.map(|&h| my_u8_to_char(h));
+        view[self.start] = 'S';
+        view[self.end] = 'E';
+        view.display(|&c| c).fmt(f)
     }
 }
 
@@ -290,87 +141,55 @@ fn my_u8_to_char(n: u8) -> char {
     (n + b'a') as char
 }
 
-/// An error returned when parsing a `bool` using [`from_str`] fails
-///
-/// [`from_str`]: super::FromStr::from_str
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ParseGridError;
-
-impl fmt::Display for ParseGridError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "provided string was not in allow list")
-    }
-}
-
-impl Error for ParseGridError {
-    #[allow(deprecated)]
-    fn description(&self) -> &str {
-        "failed to parse cmd"
-    }
-}
-
 impl FromStr for HeightMap {
-    type Err = ParseGridError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut start = Position::default();
         let mut end = Position::default();
-        let grid = s
-            .split('\n')
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .enumerate()
-            .map(|(row, line)| {
-                line.chars()
-                    .enumerate()
-                    .map(|(col, c)| match c {
-                        'S' => {
-                            start = Position { x: col, y: row };
-                            my_char_to_u8('a')
-                        }
-                        'E' => {
-                            end = Position { x: col, y: row };
-                            my_char_to_u8('z')
-                        }
-                        _ => my_char_to_u8(c),
-                    })
-                    .collect::<Vec<u8>>()
-            })
-            .collect::<Vec<Vec<u8>>>();
-        Ok(Self {
-            start,
-            end: Some(end),
-            grid,
-        })
+        let grid = Grid::parse(Day12::DAY, s, "a height, `S` or `E`", |pos, c| match c {
+            'S' => {
+                start = pos;
+                Some(my_char_to_u8('a'))
+            }
+            'E' => {
+                end = pos;
+                Some(my_char_to_u8('z'))
+            }
+            'a'..='z' => Some(my_char_to_u8(c)),
+            _ => None,
+        })?;
+        Ok(Self { start, end, grid })
     }
 }
 
+/// The map of `input` with the shortest climb from the start drawn over it.
+pub fn picture(input: &str) -> impl Render {
+    let hm: HeightMap = parse::or_panic(input.parse());
+    hm.render_path(&hm.shortest_path([hm.start]))
+}
+
 fn first(input: &str) -> usize {
-    let hm: HeightMap = input.parse().unwrap();
-    let climb_path = hm.shortest_path();
+    let hm: HeightMap = parsing(|| parse::or_panic(input.parse()));
+    let climb_path = hm.shortest_path([hm.start]);
     climb_path.inner.len()
 }
 
 fn second(input: &str) -> usize {
-    let origin: HeightMap = input.parse().unwrap();
-    let grid = origin
+    let hm: HeightMap = parsing(|| parse::or_panic(input.parse()));
+    let lowest = hm
         .grid
         .iter()
-        .map(|i| i.iter().map(|j| my_char_to_u8('z') - j).collect())
-        .collect();
-    let hm = HeightMap {
-        start: origin.end.unwrap(),
-        end: None,
-        grid,
-    };
-    let climb_path = hm.shortest_path();
+        .filter(|(_, h)| **h == my_char_to_u8('a'))
+        .map(|(pos, _)| pos);
+    let climb_path = hm.shortest_path(lowest);
     climb_path.inner.len()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     #[test]
     fn test_first() {
@@ -381,4 +200,40 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 29);
     }
+
+    #[test]
+    fn test_search_agree() {
+        let hm: HeightMap = INPUT.parse().unwrap();
+        let goal = |p: &Position| *p == hm.end;
+        let weighted = |p: &Position| hm.climbable(p).map(|n| (n, 1));
+        let bfs = search::bfs([hm.start], |p| hm.climbable(p), goal).unwrap();
+        let dijkstra = search::dijkstra([hm.start], weighted, goal).unwrap();
+        let astar = search::astar([hm.start], weighted, |p| p.manhattan(hm.end), goal).unwrap();
+        assert_eq!(bfs.cost, 31);
+        assert_eq!(dijkstra.cost, 31);
+        assert_eq!(astar.cost, 31);
+        assert_eq!(hm.shortest_path([hm.start]).inner, bfs.nodes[1..]);
+    }
+
+    #[test]
+    fn test_render() {
+        let hm: HeightMap = INPUT.parse().unwrap();
+        let image = hm.render();
+        assert_eq!((image.width(), image.height()), (8, 5));
+        assert_eq!(image[hm.start], START);
+        assert_eq!(image[hm.end], END);
+        // `a` and `z`
+        assert_eq!(image[Position::new(1, 0)], LOWEST);
+        assert_eq!(image[Position::new(4, 2)], HIGHEST);
+        let path = hm.shortest_path([hm.start]);
+        let image = hm.render_path(&path);
+        assert_eq!(image.iter().filter(|(_, c)| **c == PATH).count(), 32);
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day12.run_part1(&input), Answer::Integer(31));
+        assert_eq!(Day12.run_part2(&input), Answer::Integer(29));
+    }
 }
@@ -1,4 +1,10 @@
-use std::{cell::RefCell, cmp::Ordering, error::Error, fmt, rc::Rc, str::FromStr};
+use std::{cell::RefCell, cmp::Ordering, fmt, rc::Rc, str::FromStr};
+
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    parse::{self, ParseError},
+};
 
 #[allow(dead_code)]
 static INPUT: &str = r#"
@@ -27,10 +33,18 @@ static INPUT: &str = r#"
 [1,[2,[3,[4,[5,6,0]]]],8,9]
 "#;
 
-pub fn run() {
-    let input = include_str!("../input/day13/input");
-    dbg!(first(input));
-    dbg!(second(input));
+pub struct Day13;
+
+impl Solver for Day13 {
+    const DAY: u32 = 13;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
+    }
 }
 
 pub struct Signal {
@@ -136,7 +150,7 @@ fn init_tree(s: &str) -> Rc<RefCell<TreeNode>> {
     let mut value_stack = vec![];
     let mut level = 0;
     let mut prev_char = ' ';
-    for (_, c) in chars.iter().enumerate()
+    for c in chars.iter()
     // .filter(|(idx, _)| *idx > 0 && *idx + 1 < chars.len())
     {
         if c.is_numeric() && !value_stack.is_empty() {
@@ -182,50 +196,88 @@ fn init_tree(s: &str) -> Rc<RefCell<TreeNode>> {
     temp_root.children[0].clone()
 }
 
+/// Check that `s` is a single list with balanced brackets before building its tree.
+fn check_packet(s: &str) -> Result<(), ParseError> {
+    if !s.starts_with('[') {
+        return Err(ParseError::within(
+            Day13::DAY,
+            s,
+            s,
+            "a packet starting with `[`",
+        ));
+    }
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        let found = &s[i..i + c.len_utf8()];
+        if depth == 0 && i > 0 {
+            return Err(ParseError::within(
+                Day13::DAY,
+                s,
+                found,
+                "the end of the packet",
+            ));
+        }
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' | '0'..='9' => {}
+            _ => {
+                return Err(ParseError::within(
+                    Day13::DAY,
+                    s,
+                    found,
+                    "`[`, `]`, `,` or a digit",
+                ));
+            }
+        }
+    }
+    if depth > 0 {
+        return Err(ParseError::end_of(Day13::DAY, s, "`]`"));
+    }
+    Ok(())
+}
+
+fn parse_packet(s: &str) -> Result<Rc<RefCell<TreeNode>>, ParseError> {
+    check_packet(s)?;
+    Ok(init_tree(s))
+}
+
 impl FromStr for Signal {
-    type Err = ParseSignalError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let splited: Vec<&str> = s
+        let mut packets = s
             .split('\n')
             .map(|i| i.trim())
             .filter(|i| !i.is_empty())
-            .collect();
+            .map(|i| parse_packet(i).map_err(|e| e.rebase(s, i)));
+        let mut next = || {
+            packets
+                .next()
+                .unwrap_or_else(|| Err(ParseError::end_of(Day13::DAY, s, "a packet")))
+        };
         Ok(Self {
-            left: init_tree(splited[0]),
-            right: init_tree(splited[1]),
+            left: next()?,
+            right: next()?,
         })
     }
 }
 
-/// An error returned when parsing a `bool` using [`from_str`] fails
-///
-/// [`from_str`]: super::FromStr::from_str
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ParseSignalError;
-
-impl fmt::Display for ParseSignalError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "provided string was not a signal")
-    }
-}
-
-impl Error for ParseSignalError {
-    #[allow(deprecated)]
-    fn description(&self) -> &str {
-        "failed to parse signal"
-    }
-}
-
 fn first(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
+    let signals: Vec<Signal> = parsing(|| {
+        parse::or_panic(
+            input
+                .split("\n\n")
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse().map_err(|e: ParseError| e.rebase(input, s)))
+                .collect(),
+        )
+    });
+    signals
+        .into_iter()
         .enumerate()
-        .filter_map(|(idx, s)| {
-            let signal: Signal = s.parse().unwrap();
+        .filter_map(|(idx, signal)| {
             let o = signal.left.cmp(&signal.right);
             assert_ne!(o, Ordering::Equal);
             if let Ordering::Less = o {
@@ -242,20 +294,18 @@ fn second(input: &str) -> usize {
         [[2]]
         [[6]]
     "#;
-    let mut tree_nodes: Vec<Rc<RefCell<TreeNode>>> = input
-        .split('\n')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(init_tree)
-        .collect();
-    let divider_packets: Vec<Rc<RefCell<TreeNode>>> = divider_packets_str
-        .split('\n')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(init_tree)
-        .collect();
+    let (mut tree_nodes, divider_packets) = parsing(|| {
+        let tree_nodes: Vec<Rc<RefCell<TreeNode>>> =
+            parse::or_panic(parse::each_line(input, parse_packet));
+        let divider_packets: Vec<Rc<RefCell<TreeNode>>> = divider_packets_str
+            .split('\n')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(init_tree)
+            .collect();
+        (tree_nodes, divider_packets)
+    });
     tree_nodes.extend(divider_packets.clone());
-    dbg!(&divider_packets.len());
     tree_nodes.sort();
     tree_nodes
         .iter()
@@ -273,6 +323,7 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     #[test]
     fn test_init_tree_1() {
@@ -413,6 +464,22 @@ mod tests {
         assert!(s.left > s.right);
     }
 
+    #[test]
+    fn test_parse_error() {
+        let error = "[1,[2]]\n[1,x]".parse::<Signal>().err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "day 13, line 2, column 4: expected `[`, `]`, `,` or a digit, found `x`"
+        );
+        let error = "[1,[2]]\n  [[1]".parse::<Signal>().err().unwrap();
+        assert_eq!((error.line, error.column), (Some(2), Some(7)));
+        assert_eq!(error.expected, "`]`");
+        let error = "[1]]".parse::<Signal>().err().unwrap();
+        assert_eq!(error.expected, "the end of the packet");
+        let error = "[1]".parse::<Signal>().err().unwrap();
+        assert_eq!(error.expected, "a packet");
+    }
+
     #[test]
     fn test_first() {
         assert_eq!(first(INPUT), 13);
@@ -422,4 +489,11 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 140);
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day13.run_part1(&input), Answer::Integer(13));
+        assert_eq!(Day13.run_part2(&input), Answer::Integer(140));
+    }
 }
@@ -1,78 +1,38 @@
 use std::{
-    cmp::Ordering,
     fmt::{self, Debug},
     ops::Deref,
 };
 
+use crate::{
+    Answer, Solver,
+    animate::{Observer, Recorder},
+    bench::parsing,
+    geometry::Point,
+    grid::Grid,
+    render::{Render, Rgb},
+};
+
 #[allow(dead_code)]
 static INPUT: &str = r#"
 498,4 -> 498,6 -> 496,6
 503,4 -> 502,4 -> 502,9 -> 494,9
 "#;
 
-pub fn run() {
-    let input = include_str!("../input/day14/input");
-    dbg!(first(input));
-    dbg!(second(input));
-}
+pub struct Day14;
 
-#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
-pub struct Position {
-    x: usize,
-    y: usize,
-}
+impl Solver for Day14 {
+    const DAY: u32 = 14;
 
-impl Ord for Position {
-    // Reading order: Y then X
-    fn cmp(&self, other: &Self) -> Ordering {
-        (self.y, self.x).cmp(&(other.y, other.x))
-    }
-}
-
-impl PartialOrd for Position {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Position {
-    pub fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
-
-    #[inline]
-    pub fn distance_squared(&self, other: Self) -> usize {
-        let dx = if self.x > other.x {
-            self.x - other.x
-        } else {
-            other.x - self.x
-        };
-        let dy = if self.y > other.y {
-            self.y - other.y
-        } else {
-            other.y - self.y
-        };
-        dx * dx + dy * dy
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
     }
-}
 
-impl From<(usize, usize)> for Position {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
     }
 }
 
-impl From<&(usize, usize)> for Position {
-    fn from(value: &(usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
-}
+type Position = Point<usize>;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum State {
@@ -87,11 +47,13 @@ pub enum State {
 #[derive(Debug, Clone, Default)]
 pub struct MineMap {
     start: Position,
-    grid: Vec<Vec<State>>,
+    grid: Grid<State>,
     left_edge: usize,
     right_edge: usize,
     height: usize,
     floor: Option<usize>,
+    /// The grain on its way down.
+    falling: Option<Position>,
 }
 
 fn line_to_points(one: Position, two: Position) -> Vec<Position> {
@@ -128,30 +90,13 @@ fn to_rock_points(s: &str) -> Vec<Position> {
 }
 
 impl MineMap {
-    #[inline]
-    fn row_len(&self) -> usize {
-        self.grid[0].len()
-    }
-
-    #[allow(dead_code)]
-    #[inline]
-    fn col_len(&self) -> usize {
-        self.grid.len()
-    }
-
-    #[inline]
-    fn pos_item(&self, pos: impl Into<Position>) -> State {
-        let p = pos.into();
-        self.grid[p.y][p.x]
-    }
-
     #[inline]
     fn update_point(&mut self, pos: impl Into<Position>, state: State) {
         let pos = pos.into();
         self.left_edge = self.left_edge.min(pos.x);
         self.right_edge = self.right_edge.max(pos.x);
         self.height = self.height.max(pos.y);
-        self.grid[pos.y][pos.x] = state;
+        self.grid[pos] = state;
     }
 
     #[inline]
@@ -163,7 +108,7 @@ impl MineMap {
     }
 
     #[inline]
-    fn with_grid(mut self, grid: Vec<Vec<State>>) -> Self {
+    fn with_grid(mut self, grid: Grid<State>) -> Self {
         self.grid = grid;
         self
     }
@@ -171,18 +116,12 @@ impl MineMap {
     #[inline]
     fn update_floor(&mut self) {
         let floor = self.height + 2;
-        for i in 0..self.row_len() {
-            *self.pos_item_mut((i, floor)) = State::Rock;
+        for i in 0..self.grid.width() {
+            self.grid[Position::new(i, floor)] = State::Rock;
         }
         self.floor = Some(self.height + 2);
     }
 
-    #[inline]
-    fn pos_item_mut(&mut self, pos: impl Into<Position>) -> &mut State {
-        let p = pos.into();
-        &mut self.grid[p.y][p.x]
-    }
-
     fn update_rock_path(&mut self, s: &str) {
         let rock_points = to_rock_points(s);
         for p in rock_points {
@@ -201,27 +140,38 @@ impl MineMap {
         }
         possible_pos
             .into_iter()
-            .find(|&p| self.pos_item(p) == State::Air)
+            .find(|&p| self.grid[p] == State::Air)
     }
 
     fn run_once(&mut self) -> Option<Position> {
+        self.run_once_observed(&mut ())
+    }
+
+    /// [`MineMap::run_once`], showing the map to `observer` after every move of the grain and
+    /// once it comes to rest.
+    fn run_once_observed(&mut self, observer: &mut impl Observer<Self>) -> Option<Position> {
         let mut cur = self.start;
-        if self.pos_item(cur) == State::RestSand {
+        if self.grid[cur] == State::RestSand {
             return None;
         }
         while let Some(p) = self.fall_path(&cur) {
             if self.floor.is_none() && p.y > self.height {
+                self.falling = None;
                 return None;
             }
-            cur = p
+            cur = p;
+            self.falling = Some(cur);
+            observer.observe(self);
         }
-        *self.pos_item_mut(cur) = State::RestSand;
+        self.falling = None;
+        self.grid[cur] = State::RestSand;
+        observer.observe(self);
         Some(cur)
     }
 }
 
 impl Deref for MineMap {
-    type Target = Vec<Vec<State>>;
+    type Target = Grid<State>;
 
     fn deref(&self) -> &Self::Target {
         &self.grid
@@ -243,7 +193,7 @@ impl fmt::Display for MineMap {
             for row in self.left_edge.wrapping_sub(context)..=self.right_edge.wrapping_add(context)
             {
                 let pos = Position::new(row, col);
-                let item = self.pos_item(pos);
+                let item = self.grid[pos];
                 match item {
                     State::Air => write!(f, ".")?,
                     State::Rock => write!(f, "#")?,
@@ -258,9 +208,39 @@ impl fmt::Display for MineMap {
     }
 }
 
+impl State {
+    /// Palette of [`Render`].
+    fn colour(self) -> Rgb {
+        match self {
+            State::Air => Rgb(20, 20, 30),
+            State::Rock => Rgb(120, 110, 100),
+            State::Sand => Rgb(255, 160, 40),
+            State::RestSand => Rgb(230, 200, 120),
+            State::Start => Rgb(220, 40, 40),
+        }
+    }
+}
+
+impl Render for MineMap {
+    fn size(&self) -> (usize, usize) {
+        (self.grid.width(), self.grid.height())
+    }
+
+    fn colour(&self, pos: Position) -> Rgb {
+        if self.falling == Some(pos) {
+            return State::Sand.colour();
+        }
+        self.grid[pos].colour()
+    }
+
+    fn background(&self) -> Rgb {
+        State::Air.colour()
+    }
+}
+
 fn init_map(input: &str) -> MineMap {
     let mut mm = MineMap::default()
-        .with_grid(vec![vec![State::default(); 1000]; 300])
+        .with_grid(Grid::new(1000, 300, State::default()))
         .with_start((500, 0));
     for s in input
         .lines()
@@ -271,8 +251,22 @@ fn init_map(input: &str) -> MineMap {
     mm
 }
 
-fn first(input: &str) -> usize {
+/// The cave of `input` once the sand of part 1 has come to rest.
+pub fn picture(input: &str) -> impl Render {
     let mut mm = init_map(input);
+    while mm.run_once().is_some() {}
+    mm
+}
+
+/// Record the sand of part 1 falling into the cave of `input`, ending once it is all at rest.
+pub fn animate(input: &str, recorder: &mut Recorder) {
+    let mut mm = init_map(input);
+    while mm.run_once_observed(recorder).is_some() {}
+    recorder.capture(&mm);
+}
+
+fn first(input: &str) -> usize {
+    let mut mm = parsing(|| init_map(input));
 
     let mut result = vec![];
     while let Some(p) = mm.run_once() {
@@ -282,7 +276,7 @@ fn first(input: &str) -> usize {
 }
 
 fn second(input: &str) -> usize {
-    let mut mm = init_map(input);
+    let mut mm = parsing(|| init_map(input));
     mm.update_floor();
 
     let mut result = vec![];
@@ -295,6 +289,7 @@ fn second(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, input::crlf};
 
     #[test]
     fn test_first() {
@@ -305,4 +300,43 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT), 93);
     }
+
+    #[test]
+    fn test_render() {
+        let mut mm = init_map(INPUT);
+        while mm.run_once().is_some() {}
+        let image = mm.render();
+        assert_eq!((image.width(), image.height()), (1000, 300));
+        assert_eq!(image[Position::new(498, 4)], State::Rock.colour());
+        assert_eq!(image[Position::new(500, 8)], State::RestSand.colour());
+        assert_eq!(image[Position::new(0, 0)], State::Air.colour());
+    }
+
+    #[test]
+    fn test_animate() {
+        let mut mm = init_map(INPUT);
+        let mut recorder = Recorder::default().with_frame_skip(10);
+        let mut steps = 0usize;
+        let mut both = |mm: &MineMap| {
+            steps += 1;
+            recorder.observe(mm);
+        };
+        while mm.run_once_observed(&mut both).is_some() {}
+        assert_eq!(recorder.len(), steps.div_ceil(10));
+        // start, rocks and sand span x = 494 to 503 and y = 0 to 9, plus the margin
+        let frames = recorder.frames();
+        assert!(frames.iter().all(|f| (f.width(), f.height()) == (12, 12)));
+        assert_eq!(frames[0][Point::new(7, 1)], State::Start.colour());
+        assert_eq!(frames[0][Point::new(7, 2)], State::Sand.colour());
+        let last = frames.last().unwrap();
+        assert_eq!(last[Point::new(5, 5)], State::Rock.colour());
+        assert_eq!(last[Point::new(0, 0)], State::Air.colour());
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day14.run_part1(&input), Answer::Integer(24));
+        assert_eq!(Day14.run_part2(&input), Answer::Integer(93));
+    }
 }
@@ -1,10 +1,19 @@
 use std::{
-    cmp::Ordering,
     collections::{HashMap, HashSet},
     fmt::{self, Debug},
     ops::Deref,
 };
 
+use crate::{
+    Answer, Solver,
+    bench::parsing,
+    combinator::{self, Parser, int, lines, literal, map, preceded},
+    geometry::Point,
+    interval::IntervalSet,
+    parse,
+    render::{Render, Rgb},
+};
+
 #[allow(dead_code)]
 static INPUT: &str = r#"
 Sensor at x=2, y=18: closest beacon is at x=-2, y=15
@@ -23,17 +32,18 @@ Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3
 "#;
 
-pub fn run() {
-    let input = include_str!("../input/day15/input");
-    // let mm = init_map(input);
-    // dbg!(mm);
-    dbg!(first(input, 2000000));
-    dbg!(second(input, 4000000));
-
-    // let mm = init_map_with_empty_points(INPUT);
-    // println!("{}", mm);
-    // dbg!(first(INPUT, 10));
-    // dbg!(second(INPUT, 20));
+pub struct Day15;
+
+impl Solver for Day15 {
+    const DAY: u32 = 15;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input, 2000000).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input, 4000000).into()
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
@@ -47,6 +57,18 @@ pub struct ManhattanRect {
 }
 
 impl ManhattanRect {
+    pub fn new(center: Position, dist: usize) -> Self {
+        let idist = dist as isize;
+        Self {
+            dist,
+            center,
+            left: Position::new(center.x - idist, center.y),
+            right: Position::new(center.x + idist, center.y),
+            top: Position::new(center.x, center.y - idist),
+            down: Position::new(center.x, center.y + idist),
+        }
+    }
+
     pub fn intersect_row_x_edge(&self, row: isize) -> Option<(isize, isize)> {
         let idist = self.dist as isize;
         let x_len = idist - (row - self.center.y).abs();
@@ -58,92 +80,21 @@ impl ManhattanRect {
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
-pub struct Position {
-    x: isize,
-    y: isize,
-}
-
-impl Ord for Position {
-    // Reading order: Y then X
-    fn cmp(&self, other: &Self) -> Ordering {
-        (self.y, self.x).cmp(&(other.y, other.x))
-    }
-}
-
-impl PartialOrd for Position {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Position {
-    pub fn new(x: isize, y: isize) -> Self {
-        Self { x, y }
-    }
+type Position = Point<isize>;
 
-    #[inline]
-    pub fn manhattan_distance(&self, other: Self) -> usize {
-        ((self.x - other.x).abs() + (self.y - other.y).abs()) as usize
-    }
-
-    #[inline]
-    pub fn manhattan_rect(&self, dist: usize) -> ManhattanRect {
-        let idist = dist as isize;
-        ManhattanRect {
-            dist,
-            center: *self,
-            left: Position {
-                x: self.x - idist,
-                y: self.y,
-            },
-            right: Position {
-                x: self.x + idist,
-                y: self.y,
-            },
-            top: Position {
-                x: self.x,
-                y: self.y - idist,
-            },
-            down: Position {
-                x: self.x,
-                y: self.y + idist,
-            },
-        }
-    }
-
-    #[inline]
-    pub fn manhattan_points(&self, dist: usize) -> Vec<Self> {
-        let idist = dist as isize;
-        let mut result = vec![];
-        for x in (self.x - idist)..(self.x + idist) {
-            for y in (self.y - idist)..(self.y + idist) {
-                let pos = Position::new(x, y);
-                if &pos != self && self.manhattan_distance(pos) <= dist {
-                    result.push(pos)
-                }
+/// Cells of the diamond of radius `dist` around `center`, excluding the center itself.
+fn manhattan_points(center: Position, dist: usize) -> Vec<Position> {
+    let idist = dist as isize;
+    let mut result = vec![];
+    for x in (center.x - idist)..(center.x + idist) {
+        for y in (center.y - idist)..(center.y + idist) {
+            let pos = Position::new(x, y);
+            if pos != center && center.manhattan(pos) as usize <= dist {
+                result.push(pos)
             }
         }
-        result
-    }
-}
-
-impl From<(isize, isize)> for Position {
-    fn from(value: (isize, isize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
-}
-
-impl From<&(isize, isize)> for Position {
-    fn from(value: &(isize, isize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
     }
+    result
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -158,6 +109,8 @@ pub enum Item {
 #[derive(Debug, Clone, Default)]
 pub struct MineMap {
     items: HashMap<Position, Item>,
+    /// What each sensor rules out.
+    ranges: Vec<ManhattanRect>,
     edge: Edge,
 }
 
@@ -231,199 +184,123 @@ impl fmt::Display for MineMap {
     }
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct Line {
-    segments: Vec<Segment>,
-}
-
-impl Line {
-    pub fn new(start: isize, end: isize) -> Self {
-        let seg = Segment::new(start, end);
-        Self {
-            segments: vec![seg],
+impl Item {
+    /// Palette of [`Render`].
+    fn colour(self) -> Rgb {
+        match self {
+            Item::Unknow => Rgb(20, 20, 30),
+            Item::Beacon => Rgb(60, 140, 255),
+            Item::Sensor => Rgb(230, 50, 50),
+            Item::Empty => Rgb(90, 90, 100),
         }
     }
-
-    pub fn contains(&self, seg: impl Into<Segment>) -> bool {
-        let seg = seg.into();
-        self.segments
-            .iter()
-            .any(|s| s.contains(seg.start) && s.contains(seg.end))
-    }
-
-    pub fn add_segment(&mut self, seg: impl Into<Segment>) {
-        self.segments.push(seg.into());
-        self.reduce();
-    }
-
-    fn __reduce(&mut self) {
-        let seg_length = self.segments.len();
-        if seg_length < 2 {
-            return;
-        }
-        let mut joined = None;
-
-        let mut result = vec![];
-        for i in 0..seg_length {
-            if let Some(j) = joined {
-                if j == i {
-                    continue;
-                }
-            }
-            let left = self.segments[i];
-            if i == seg_length - 1 {
-                result.push(left);
-                continue;
-            }
-            let right = self.segments[i + 1];
-            if left.joinable(&right) {
-                result.push(left.join(&right));
-                joined = Some(i + 1);
-            } else if i == seg_length - 2 {
-                result.push(left);
-                result.push(right);
-            } else {
-                result.push(left);
-            }
-        }
-        self.segments = result;
-        if joined.is_some() {
-            self.__reduce();
-        }
-    }
-
-    pub fn reduce(&mut self) {
-        let seg_length = self.segments.len();
-        if seg_length < 2 {
-            return;
-        }
-
-        self.segments.sort();
-        self.__reduce();
-    }
-
-    pub fn empty_points(&self) -> Vec<isize> {
-        self.segments
-            .windows(2)
-            .flat_map(|v| {
-                let left = v[0];
-                let right = v[1];
-                ((left.end + 1)..right.start).collect::<Vec<isize>>()
-            })
-            .collect()
-    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Segment {
-    start: isize,
-    end: isize,
-}
-
-impl Ord for Segment {
-    // Reading order: Y then X
-    fn cmp(&self, other: &Self) -> Ordering {
-        (self.start, self.end).cmp(&(other.start, other.end))
-    }
-}
-
-impl PartialOrd for Segment {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Segment {
-    pub fn new(start: isize, end: isize) -> Self {
-        Self { start, end }
-    }
-
-    #[inline]
-    pub fn contains(&self, point: isize) -> bool {
-        self.start <= point && point <= self.end
-    }
-
-    #[inline]
-    pub fn overlap(&self, other: &Self) -> bool {
-        if self.start > other.start {
-            return other.overlap(self);
-        }
-        self.end >= other.start
-    }
-
-    #[inline]
-    pub fn joinable(&self, other: &Self) -> bool {
-        if self.start > other.start {
-            return other.joinable(self);
-        }
-        self.end >= other.start - 1
-    }
+/// Longest side of the picture of a [`MineMap`].
+const PICTURE_SIDE: usize = 1000;
 
-    #[inline]
-    pub fn join(&self, other: &Self) -> Self {
-        assert!(self.joinable(other));
-        Self::new(self.start.min(other.start), self.end.max(other.end))
+impl MineMap {
+    /// Cells of the map across each cell of the picture, the real input spans millions of them.
+    fn block(&self) -> usize {
+        let (width, height) = (
+            self.edge.left.abs_diff(self.edge.right) + 1,
+            self.edge.top.abs_diff(self.edge.down) + 1,
+        );
+        width.max(height).div_ceil(PICTURE_SIDE)
     }
 }
 
-impl From<(isize, isize)> for Segment {
-    fn from(value: (isize, isize)) -> Self {
-        Self {
-            start: value.0,
-            end: value.1,
-        }
+/// The box around the sensors and beacons, each cell of the picture standing for a square
+/// [`MineMap::block`] cells wide. A sensor or beacon shows even in a block of mostly empty
+/// cells, the rest is coloured by whether the middle of the block is ruled out.
+impl Render for MineMap {
+    fn size(&self) -> (usize, usize) {
+        let block = self.block();
+        (
+            (self.edge.left.abs_diff(self.edge.right) + 1).div_ceil(block),
+            (self.edge.top.abs_diff(self.edge.down) + 1).div_ceil(block),
+        )
+    }
+
+    fn colour(&self, pos: Point<usize>) -> Rgb {
+        let block = self.block() as isize;
+        let corner = Position::new(
+            self.edge.left + pos.x as isize * block,
+            self.edge.top + pos.y as isize * block,
+        );
+        let in_block = |p: &Position| {
+            (corner.x..corner.x + block).contains(&p.x)
+                && (corner.y..corner.y + block).contains(&p.y)
+        };
+        let middle = Position::new(corner.x + block / 2, corner.y + block / 2);
+        let item = [Item::Sensor, Item::Beacon]
+            .into_iter()
+            .find(|&item| self.iter().any(|(p, i)| *i == item && in_block(p)))
+            .unwrap_or_else(|| {
+                let ruled_out = self
+                    .ranges
+                    .iter()
+                    .any(|r| r.center.manhattan(middle) as usize <= r.dist);
+                if ruled_out { Item::Empty } else { Item::Unknow }
+            });
+        item.colour()
     }
 }
 
-impl From<&(isize, isize)> for Segment {
-    fn from(value: &(isize, isize)) -> Self {
-        Self {
-            start: value.0,
-            end: value.1,
-        }
-    }
+/// "x=-2, y=15"
+fn position<'a>() -> impl Parser<'a, Position> {
+    map(
+        (
+            preceded(literal("x="), int()),
+            preceded(literal(", y="), int()),
+        ),
+        |(x, y)| Position::new(x, y),
+    )
 }
 
-fn get_pos(s: &str) -> (Position, Position) {
-    let result: Vec<Position> = s
-        .split(':')
-        .map(|i| {
-            let splited: Vec<isize> = i
-                .split(',')
-                .map(|j| j.split('=').last().unwrap().parse::<isize>().unwrap())
-                .collect();
-            Position::new(splited[0], splited[1])
-        })
-        .collect();
-    (result[0], result[1])
+/// A sensor and its closest beacon.
+fn sensor<'a>() -> impl Parser<'a, (Position, Position)> {
+    (
+        preceded(literal("Sensor at "), position()),
+        preceded(literal(": closest beacon is at "), position()),
+    )
 }
 
-#[allow(dead_code)]
 fn init_map(input: &str) -> MineMap {
     let mut mm = MineMap::default();
-    for s in input.lines().map(|i| i.trim()).filter(|i| !i.is_empty()) {
-        let (sensor_pos, beacon_pos) = get_pos(s);
+    for (sensor_pos, beacon_pos) in to_sensors(input) {
         mm.update_point(sensor_pos, Item::Sensor);
         mm.update_point(beacon_pos, Item::Beacon);
+        let dist = sensor_pos.manhattan(beacon_pos) as usize;
+        mm.ranges.push(ManhattanRect::new(sensor_pos, dist));
     }
     mm
 }
 
+/// The sensors and beacons of `input` with the cells they rule out, scaled down to at most
+/// [`PICTURE_SIDE`] cells across.
+pub fn picture(input: &str) -> impl Render {
+    init_map(input)
+}
+
 #[allow(dead_code)]
 fn init_map_with_empty_points(input: &str) -> MineMap {
     let mut mm = MineMap::default();
     let mut sb_dist_vec = vec![];
     let mut taken_points = HashSet::new();
-    for s in input.lines().map(|i| i.trim()).filter(|i| !i.is_empty()) {
-        let (sensor_pos, beacon_pos) = get_pos(s);
+    for (sensor_pos, beacon_pos) in to_sensors(input) {
         mm.update_point(sensor_pos, Item::Sensor);
         mm.update_point(beacon_pos, Item::Beacon);
-        sb_dist_vec.push((sensor_pos, sensor_pos.manhattan_distance(beacon_pos)));
+        sb_dist_vec.push((sensor_pos, sensor_pos.manhattan(beacon_pos) as usize));
+        mm.ranges.push(ManhattanRect::new(
+            sensor_pos,
+            sensor_pos.manhattan(beacon_pos) as usize,
+        ));
         taken_points.insert(sensor_pos);
         taken_points.insert(beacon_pos);
     }
     for (s, d) in sb_dist_vec {
-        let points = s.manhattan_points(d);
+        let points = manhattan_points(s, d);
         for p in points {
             if !taken_points.contains(&p) {
                 mm.update_point(p, Item::Empty);
@@ -433,32 +310,33 @@ fn init_map_with_empty_points(input: &str) -> MineMap {
     mm
 }
 
+/// Each sensor with its closest beacon.
+fn to_sensors(input: &str) -> Vec<(Position, Position)> {
+    parsing(|| parse::or_panic(combinator::run(Day15::DAY, input, lines(sensor()))))
+}
+
 fn first(input: &str, row: isize) -> usize {
     let mut mm = MineMap::default();
     let mut manhattan_rect_vec = vec![];
     let mut taken_points = HashSet::new();
-    for s in input.lines().map(|i| i.trim()).filter(|i| !i.is_empty()) {
-        let (sensor_pos, beacon_pos) = get_pos(s);
+    for (sensor_pos, beacon_pos) in to_sensors(input) {
         mm.update_point(sensor_pos, Item::Sensor);
         mm.update_point(beacon_pos, Item::Beacon);
-        manhattan_rect_vec
-            .push(sensor_pos.manhattan_rect(sensor_pos.manhattan_distance(beacon_pos)));
+        manhattan_rect_vec.push(ManhattanRect::new(
+            sensor_pos,
+            sensor_pos.manhattan(beacon_pos) as usize,
+        ));
         taken_points.insert(sensor_pos);
         taken_points.insert(beacon_pos);
     }
-    let mut edge: Option<(isize, isize)> = None;
+    let mut covered = IntervalSet::new();
     for r in manhattan_rect_vec {
-        if let Some(x_edge) = r.intersect_row_x_edge(row) {
-            if let Some(e) = edge {
-                edge = Some((e.0.min(x_edge.0), (e.1.max(x_edge.1))));
-            } else {
-                edge = Some(x_edge);
-            }
+        if let Some((left, right)) = r.intersect_row_x_edge(row) {
+            covered.insert(left as i64..=right as i64);
         }
     }
-    let row_edge = edge.unwrap();
     let taken_count = taken_points.iter().filter(|i| i.y == row).count();
-    (row_edge.1 - row_edge.0 + 1) as usize - taken_count
+    covered.len() as usize - taken_count
 }
 
 fn second(input: &str, dist: usize) -> usize {
@@ -466,27 +344,27 @@ fn second(input: &str, dist: usize) -> usize {
     let mut mm = MineMap::default();
     let mut manhattan_rect_vec = vec![];
     let mut taken_points = HashSet::new();
-    for s in input.lines().map(|i| i.trim()).filter(|i| !i.is_empty()) {
-        let (sensor_pos, beacon_pos) = get_pos(s);
+    for (sensor_pos, beacon_pos) in to_sensors(input) {
         mm.update_point(sensor_pos, Item::Sensor);
         mm.update_point(beacon_pos, Item::Beacon);
-        manhattan_rect_vec
-            .push(sensor_pos.manhattan_rect(sensor_pos.manhattan_distance(beacon_pos)));
+        manhattan_rect_vec.push(ManhattanRect::new(
+            sensor_pos,
+            sensor_pos.manhattan(beacon_pos) as usize,
+        ));
         taken_points.insert(sensor_pos);
         taken_points.insert(beacon_pos);
     }
     for row in 0..dist {
-        let mut line = Line::default();
+        let mut covered = IntervalSet::new();
         for r in &manhattan_rect_vec {
-            if let Some(x_edge) = r.intersect_row_x_edge(row as isize) {
-                line.add_segment(x_edge);
+            if let Some((left, right)) = r.intersect_row_x_edge(row as isize) {
+                covered.insert(left as i64..=right as i64);
             }
         }
-        if !line.contains((0, idist)) {
-            for p in line.empty_points() {
-                if !taken_points.iter().any(|i| i.x == p && i.y == row as isize) {
-                    return p as usize * 4000000 + row;
-                }
+        for x in covered.gaps_within(0..=idist as i64).flatten() {
+            let pos = Position::new(x as isize, row as isize);
+            if !taken_points.contains(&pos) {
+                return x as usize * 4000000 + row;
             }
         }
     }
@@ -496,6 +374,7 @@ fn second(input: &str, dist: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{crlf, normalize};
 
     #[test]
     fn test_first() {
@@ -506,4 +385,37 @@ mod tests {
     fn test_second() {
         assert_eq!(second(INPUT, 20), 56000011);
     }
+
+    #[test]
+    fn test_render() {
+        let mm = init_map(INPUT);
+        let image = mm.render();
+        // sensors and beacons span x -2..=25 and y 0..=22
+        assert_eq!((image.width(), image.height()), (28, 23));
+        assert_eq!(image[Point::new(2 + 2, 18)], Item::Sensor.colour());
+        assert_eq!(image[Point::new(0, 15)], Item::Beacon.colour());
+        assert_eq!(image[Point::new(0, 0)], Item::Empty.colour());
+        // the distress beacon of part 2 is the one cell left in range of the search
+        assert_eq!(image[Point::new(14 + 2, 11)], Item::Unknow.colour());
+
+        let far = init_map(
+            "Sensor at x=0, y=0: closest beacon is at x=1999999, y=0
+            Sensor at x=4000000, y=3000000: closest beacon is at x=4000000, y=2999999",
+        );
+        let image = far.render();
+        assert_eq!((image.width(), image.height()), (1000, 750));
+        assert_eq!(image[Point::new(0, 0)], Item::Sensor.colour());
+        assert_eq!(image[Point::new(999, 749)], Item::Sensor.colour());
+        assert_eq!(image[Point::new(499, 0)], Item::Beacon.colour());
+        assert_eq!(image[Point::new(300, 300)], Item::Unknow.colour());
+    }
+
+    #[test]
+    fn test_crlf() {
+        // the rows asked about differ from the real input, so no going through `Puzzle`
+        let input = crlf(INPUT);
+        let input = normalize(&input);
+        assert_eq!(first(&input, 10), 26);
+        assert_eq!(second(&input, 20), 56000011);
+    }
 }
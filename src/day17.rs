@@ -1,27 +1,33 @@
 use std::{
-    cmp::Ordering,
     collections::{HashMap, HashSet},
     fmt,
     ops::Deref,
 };
 
+use crate::{
+    Answer, Solver,
+    animate::{Observer, Recorder},
+    bench::parsing,
+    cycle,
+    geometry::Point,
+    render::{Render, Rgb},
+};
+
+#[allow(dead_code)]
 static INPUT: &str = r#">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>"#;
 
-pub fn run() {
-    let input = include_str!("../input/day17/input");
-    dbg!(first(input, 2022));
-    // let mut mm = MineMap::new(7);
-    // println!("{}", &mm);
-    // mm.add_rock(Rock::Minus);
-    // println!("{}", &mm);
-
-    // mm.move_rock_item(Direction::Right);
-    // println!("{}", &mm);
-    // mm.move_rock_item(Direction::Down);
-    // println!("{}", &mm);
-
-    // dbg!(first(INPUT, 2022));
-    // dbg!(first(INPUT, 5));
+pub struct Day17;
+
+impl Solver for Day17 {
+    const DAY: u32 = 17;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input, 2022).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input, 1_000_000_000_000).into()
+    }
 }
 
 // ####
@@ -282,57 +288,61 @@ impl fmt::Display for MineMap {
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
-pub struct Edge {
-    left: usize,
-    right: usize,
-    top: usize,
-    down: usize,
-}
-
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Position {
-    x: usize,
-    y: usize,
+impl Item {
+    /// Palette of [`Render`].
+    fn colour(self) -> Rgb {
+        match self {
+            Item::Empty => Rgb(20, 20, 30),
+            Item::Wall | Item::Floor | Item::Corner => Rgb(120, 120, 130),
+            Item::Rock => Rgb(255, 140, 30),
+            Item::RestRock => Rgb(170, 130, 90),
+        }
+    }
 }
 
-impl Position {
-    pub fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
+/// The chamber with the floor at the bottom of the picture.
+impl Render for MineMap {
+    fn size(&self) -> (usize, usize) {
+        (
+            self.edge.right - self.edge.left + 1,
+            self.edge.top - self.edge.down + 1,
+        )
     }
-}
 
-impl Ord for Position {
-    // Reading order: Y then X
-    fn cmp(&self, other: &Self) -> Ordering {
-        (self.y, self.x).cmp(&(other.y, other.x))
+    fn colour(&self, pos: Position) -> Rgb {
+        let pos = Position::new(self.edge.left + pos.x, self.edge.top - pos.y);
+        let falling = self
+            .rock_item
+            .as_ref()
+            .is_some_and(|r| r.pos.contains(&pos));
+        match self.get(&pos) {
+            Some(item) => item.colour(),
+            None if falling => Item::Rock.colour(),
+            None => Item::Empty.colour(),
+        }
     }
-}
 
-impl PartialOrd for Position {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    /// The top grows as the tower does, the floor stays put.
+    fn origin(&self) -> Point<isize> {
+        Point::new(self.edge.left as isize, -(self.edge.top as isize))
     }
-}
 
-impl From<(usize, usize)> for Position {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
+    fn background(&self) -> Rgb {
+        Item::Empty.colour()
     }
 }
 
-impl From<&(usize, usize)> for Position {
-    fn from(value: &(usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Edge {
+    left: usize,
+    right: usize,
+    top: usize,
+    down: usize,
 }
 
+/// Cell of the chamber, `y` grows upwards from the floor.
+type Position = Point<usize>;
+
 #[derive(Debug, Clone, Copy)]
 pub enum Direction {
     Top,
@@ -353,29 +363,180 @@ fn to_directions(input: &str) -> Vec<Direction> {
         .collect()
 }
 
-fn first(input: &str, rocks: usize) -> usize {
-    let mut mm = MineMap::new(7);
-    let directions = to_directions(input);
-    let mut direction_iter = std::iter::repeat(directions).flatten();
-    for rock in std::iter::repeat(ALL_ROCKS).flatten().take(rocks) {
-        if mm.rock_item.is_none() {
-            mm.add_rock(rock);
+/// Rocks falling one after the other, pushed by the jets.
+#[derive(Debug, Clone)]
+struct Tower<'a> {
+    mm: MineMap,
+    directions: &'a [Direction],
+    jet: usize,
+    rocks: usize,
+}
+
+impl<'a> Tower<'a> {
+    fn new(directions: &'a [Direction]) -> Self {
+        Self {
+            mm: MineMap::new(7),
+            directions,
+            jet: 0,
+            rocks: 0,
         }
-        while mm.rock_item.is_some() {
-            let direction = direction_iter.next().unwrap();
-            mm.move_rock_item(direction);
-            mm.move_rock_item(Direction::Down);
+    }
+
+    fn drop_rock(&mut self) {
+        self.drop_rock_observed(&mut ());
+    }
+
+    /// [`Tower::drop_rock`], showing the chamber to `observer` when the rock appears and after
+    /// every push and fall.
+    fn drop_rock_observed(&mut self, observer: &mut impl Observer<MineMap>) {
+        self.mm.add_rock(ALL_ROCKS[self.rocks % ALL_ROCKS.len()]);
+        observer.observe(&self.mm);
+        while self.mm.rock_item.is_some() {
+            self.mm.move_rock_item(self.directions[self.jet]);
+            observer.observe(&self.mm);
+            self.jet = (self.jet + 1) % self.directions.len();
+            self.mm.move_rock_item(Direction::Down);
+            observer.observe(&self.mm);
+        }
+        self.rocks += 1;
+    }
+
+    /// Depth of the highest rock of each column below the top of the tower.
+    fn skyline(&self) -> [usize; 7] {
+        let mut depths = [0; 7];
+        for (x, depth) in (1..).zip(&mut depths) {
+            *depth = (0..=self.mm.highest)
+                .rev()
+                .position(|y| self.mm.contains_key(&Position::new(x, y)))
+                .unwrap();
         }
+        depths
+    }
+
+    /// What decides where the next rocks land: the next rock, the next jet and the shape of
+    /// the top of the tower.
+    fn key(&self) -> (usize, usize, [usize; 7]) {
+        (self.rocks % ALL_ROCKS.len(), self.jet, self.skyline())
+    }
+}
+
+/// Record the rocks of part 1 falling into the chamber, ending on the tower they build.
+pub fn animate(input: &str, recorder: &mut Recorder) {
+    let directions = to_directions(input);
+    let mut tower = Tower::new(&directions);
+    for _ in 0..2022 {
+        tower.drop_rock_observed(recorder);
+    }
+    recorder.capture(&tower.mm);
+}
+
+/// The tower of `input` once the rocks of part 1 have fallen.
+pub fn picture(input: &str) -> impl Render {
+    let directions = to_directions(input);
+    let mut tower = Tower::new(&directions);
+    for _ in 0..2022 {
+        tower.drop_rock();
+    }
+    tower.mm
+}
+
+fn first(input: &str, rocks: usize) -> usize {
+    let directions = parsing(|| to_directions(input));
+    let mut tower = Tower::new(&directions);
+    for _ in 0..rocks {
+        tower.drop_rock();
+    }
+    tower.mm.highest
+}
+
+fn second(input: &str, rocks: usize) -> usize {
+    let directions = parsing(|| to_directions(input));
+    let mut tower = Tower::new(&directions);
+    let mut heights = vec![0];
+    let cycle = cycle::find_cycle(
+        &mut tower,
+        Tower::key,
+        |t| {
+            t.drop_rock();
+            heights.push(t.mm.highest);
+        },
+        rocks,
+    );
+    match cycle {
+        Some(cycle) => cycle.extrapolate(rocks, |i| heights[i] as i64) as usize,
+        None => heights[rocks],
     }
-    mm.highest
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Puzzle, grid::Grid, input::crlf};
 
     #[test]
     fn test_first() {
         assert_eq!(first(INPUT, 2022), 3068);
     }
+
+    #[test]
+    fn test_second() {
+        assert_eq!(second(INPUT, 2022), 3068);
+        assert_eq!(second(INPUT, 1_000_000_000_000), 1514285714288);
+    }
+
+    #[test]
+    fn test_render() {
+        let directions = to_directions(INPUT);
+        let mut tower = Tower::new(&directions);
+        tower.drop_rock();
+        tower.mm.add_rock(Rock::Plus);
+        let image = tower.mm.render();
+        let bottom = image.height() - 1;
+        assert_eq!(image.width(), 9);
+        assert_eq!(image[Position::new(4, bottom)], Item::Floor.colour());
+        // the first rock rests right above the floor, from x = 3 to 6
+        let row = image.row(bottom - 1);
+        assert_eq!(row[3..7], [Item::RestRock.colour(); 4]);
+        assert_eq!(row[2], Item::Empty.colour());
+        // the plus appears above the tower, falling
+        let falling = image
+            .iter()
+            .filter(|(_, c)| **c == Item::Rock.colour())
+            .count();
+        assert_eq!(falling, 5);
+    }
+
+    #[test]
+    fn test_animate() {
+        let directions = to_directions(INPUT);
+        let mut tower = Tower::new(&directions);
+        let mut recorder = Recorder::default().with_margin(0);
+        for _ in 0..3 {
+            tower.drop_rock_observed(&mut recorder);
+        }
+        assert_eq!(tower.mm.highest, 6);
+        let frames = recorder.frames();
+        let count = |image: &Grid<Rgb>, item: Item| {
+            image.iter().filter(|(_, c)| **c == item.colour()).count()
+        };
+        // the chamber grew, yet the floor is at the bottom of every frame
+        assert!(frames.iter().all(|f| f.width() == 9));
+        assert!(
+            frames
+                .iter()
+                .all(|f| f.row(f.height() - 1)[4] == Item::Floor.colour())
+        );
+        assert_eq!(count(&frames[0], Item::Rock), 4);
+        assert_eq!(count(&frames[0], Item::RestRock), 0);
+        let last = frames.last().unwrap();
+        assert_eq!(count(last, Item::Rock), 0);
+        assert_eq!(count(last, Item::RestRock), 4 + 5 + 5);
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = crlf(INPUT);
+        assert_eq!(Day17.run_part1(&input), Answer::Integer(3068));
+        assert_eq!(Day17.run_part2(&input), Answer::Integer(1514285714288));
+    }
 }
@@ -0,0 +1,74 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{client::Client, input};
+
+/// What [`fetch`] did for a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    /// The input was downloaded to the path.
+    Downloaded(PathBuf),
+    /// The input was already on disk, nothing was requested.
+    Cached(PathBuf),
+}
+
+/// Download the input of `day` to `input/dayNN/input` below `root`, unless it is there already.
+///
+/// `client` is only built when a download is needed, so a cached input needs no session token.
+pub fn fetch(client: impl FnOnce() -> Result<Client>, root: &Path, day: u32) -> Result<Fetched> {
+    let path = root.join(input::default_path(day));
+    if fs::metadata(&path).is_ok_and(|m| m.len() > 0) {
+        return Ok(Fetched::Cached(path));
+    }
+    let (status, body) = client()?.get(day, "/input")?;
+    match status {
+        200 => {}
+        404 => bail!("the input of day {day} is not available yet"),
+        400 | 500 => bail!("the input of day {day} was refused, is the session token still valid?"),
+        _ => bail!("unexpected status {status} fetching the input of day {day}"),
+    }
+    let dir = root.join(input::day_dir(day));
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    fs::write(&path, body).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(Fetched::Downloaded(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub;
+
+    #[test]
+    fn test_fetch() {
+        let (url, server) = stub::serve(vec![(200, "1\n2\n"), (404, "Not Found")]);
+        let root = stub::temp_root("fetch");
+        let client = || Ok(Client::new(&url, "abc", &root).with_min_interval(Default::default()));
+
+        let path = root.join("input/day01/input");
+        assert_eq!(
+            fetch(client, &root, 1).unwrap(),
+            Fetched::Downloaded(path.clone())
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1\n2\n");
+        // served from disk, the stub would answer 404 otherwise
+        assert_eq!(
+            fetch(client, &root, 1).unwrap(),
+            Fetched::Cached(path.clone())
+        );
+        // no client, e.g. without a session token, is needed for that
+        let no_client = || bail!("no session token");
+        assert_eq!(fetch(no_client, &root, 1).unwrap(), Fetched::Cached(path));
+        assert!(fetch(no_client, &root, 2).is_err());
+        assert!(fetch(client, &root, 2).is_err());
+        assert!(!root.join("input/day02/input").exists());
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("GET /2022/day/2/input "));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, Sub},
+};
+
+/// Integer types usable as [`Point`] coordinates.
+pub trait Coord:
+    Copy + Default + Debug + Ord + Hash + Add<Output = Self> + Sub<Output = Self>
+{
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    /// Add a signed offset, `None` when the result does not fit.
+    fn checked_offset(self, delta: isize) -> Option<Self>;
+    /// Distance between two values on the axis.
+    fn distance(self, other: Self) -> Self;
+}
+
+macro_rules! impl_coord {
+    (unsigned: $($t:ty),*) => {$(
+        impl Coord for $t {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+
+            fn checked_offset(self, delta: isize) -> Option<Self> {
+                let magnitude = <$t>::try_from(delta.unsigned_abs()).ok()?;
+                if delta < 0 {
+                    self.checked_sub(magnitude)
+                } else {
+                    self.checked_add(magnitude)
+                }
+            }
+
+            fn distance(self, other: Self) -> Self {
+                self.abs_diff(other)
+            }
+        }
+    )*};
+    (signed: $($t:ty),*) => {$(
+        impl Coord for $t {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+
+            fn checked_offset(self, delta: isize) -> Option<Self> {
+                self.checked_add(<$t>::try_from(delta).ok()?)
+            }
+
+            fn distance(self, other: Self) -> Self {
+                (self - other).abs()
+            }
+        }
+    )*};
+}
+
+impl_coord!(unsigned: u8, u16, u32, u64, usize);
+impl_coord!(signed: i8, i16, i32, i64, isize);
+
+/// One step on a grid, in screen coordinates: `Up` decreases `y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// `(dx, dy)` of one step.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+/// Offsets of the 4 orthogonal neighbours.
+pub const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Offsets of the 8 neighbours including diagonals, in reading order.
+pub const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A point on a grid, ordered in reading order: by `y`, then by `x`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Coord> Point<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    /// Sum of the distances along each axis.
+    pub fn manhattan(&self, other: Self) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y)
+    }
+
+    /// Largest of the distances along each axis, the number of king moves between the points.
+    pub fn chebyshev(&self, other: Self) -> T {
+        self.x.distance(other.x).max(self.y.distance(other.y))
+    }
+
+    pub fn checked_add(&self, other: Self) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_add(other.x)?,
+            self.y.checked_add(other.y)?,
+        ))
+    }
+
+    pub fn checked_sub(&self, other: Self) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_sub(other.x)?,
+            self.y.checked_sub(other.y)?,
+        ))
+    }
+
+    /// The point moved by `(dx, dy)`, `None` if it leaves the range of `T`.
+    pub fn offset(&self, (dx, dy): (isize, isize)) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_offset(dx)?,
+            self.y.checked_offset(dy)?,
+        ))
+    }
+
+    /// The neighbouring point in `direction`, `None` if it leaves the range of `T`.
+    pub fn step(&self, direction: Direction) -> Option<Self> {
+        self.offset(direction.offset())
+    }
+
+    /// Orthogonal neighbours that fit in `T`.
+    pub fn neighbours4(self) -> impl Iterator<Item = Self> {
+        NEIGHBOURS_4.into_iter().filter_map(move |d| self.offset(d))
+    }
+
+    /// Orthogonal and diagonal neighbours that fit in `T`.
+    pub fn neighbours8(self) -> impl Iterator<Item = Self> {
+        NEIGHBOURS_8.into_iter().filter_map(move |d| self.offset(d))
+    }
+}
+
+impl<T: Coord> Ord for Point<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.y, self.x).cmp(&(other.y, other.x))
+    }
+}
+
+impl<T: Coord> PartialOrd for Point<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Coord> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Coord> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: Copy> From<&(T, T)> for Point<T> {
+    fn from(&(x, y): &(T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let a = Point::new(1isize, -2);
+        let b = Point::new(-3, 4);
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert_eq!(Point::new(5usize, 1).manhattan(Point::new(2, 3)), 5);
+    }
+
+    #[test]
+    fn test_checked() {
+        let p = Point::new(0usize, 3);
+        assert_eq!(p.checked_sub(Point::new(1, 0)), None);
+        assert_eq!(p.checked_add(Point::new(2, 1)), Some(Point::new(2, 4)));
+        assert_eq!(p.step(Direction::Left), None);
+        assert_eq!(p.step(Direction::Up), Some(Point::new(0, 2)));
+        assert_eq!(Point::new(i8::MAX, 0).step(Direction::Right), None);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let corner = Point::new(0usize, 0);
+        let n: Vec<_> = corner.neighbours4().collect();
+        assert_eq!(n, [Point::new(1, 0), Point::new(0, 1)]);
+        assert_eq!(corner.neighbours8().count(), 3);
+        assert_eq!(Point::new(0isize, 0).neighbours8().count(), 8);
+        let mut sorted: Vec<_> = Point::new(1usize, 1).neighbours8().collect();
+        sorted.sort();
+        assert_eq!(
+            sorted,
+            Point::new(1usize, 1).neighbours8().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_reading_order() {
+        assert!(Point::new(5, 0) < Point::new(0, 1));
+        assert!(Point::new(0, 1) < Point::new(1, 1));
+    }
+}
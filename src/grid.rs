@@ -0,0 +1,275 @@
+use std::{
+    fmt, iter,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    geometry::{Direction, NEIGHBOURS_4, NEIGHBOURS_8, Point},
+    parse::ParseError,
+};
+
+/// Rectangular grid of cells stored row by row, addressed by `Point { x: column, y: row }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Grid of `width` columns and `height` rows, every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Grid of `width` columns holding `cells` in reading order.
+    ///
+    /// Panics if the number of cells is not a multiple of `width`.
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Self {
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        assert_eq!(width * height, cells.len(), "cells do not fill whole rows");
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parse one cell per character, one row per line. Lines are trimmed and blank lines
+    /// skipped, so indented examples parse as well.
+    ///
+    /// `cell` gets the position and character of each cell, `None` rejects the character and
+    /// the error tells `expected` instead.
+    pub fn parse(
+        day: u32,
+        s: &str,
+        expected: &str,
+        mut cell: impl FnMut(Point<usize>, char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut cells = vec![];
+        for (y, line) in s
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .enumerate()
+        {
+            let start = cells.len();
+            for (x, (i, c)) in line.char_indices().enumerate() {
+                match cell(Point::new(x, y), c) {
+                    Some(value) => cells.push(value),
+                    None => {
+                        let found = &line[i..i + c.len_utf8()];
+                        return Err(ParseError::within(day, s, found, expected));
+                    }
+                }
+            }
+            let len = cells.len() - start;
+            match width {
+                None => width = Some(len),
+                Some(w) if w != len => {
+                    let expected = format!("a row of {w} cells");
+                    return Err(ParseError::within(day, s, line, expected));
+                }
+                _ => {}
+            }
+        }
+        Ok(Self::from_cells(width.unwrap_or(0), cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Point<usize>) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    fn index_of(&self, pos: Point<usize>) -> Option<usize> {
+        self.contains(pos).then(|| pos.y * self.width + pos.x)
+    }
+
+    pub fn get(&self, pos: Point<usize>) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, pos: Point<usize>) -> Option<&mut T> {
+        self.index_of(pos).map(|i| &mut self.cells[i])
+    }
+
+    /// The neighbouring position in `direction`, `None` past the edge.
+    pub fn step(&self, pos: Point<usize>, direction: Direction) -> Option<Point<usize>> {
+        pos.step(direction).filter(|p| self.contains(*p))
+    }
+
+    /// Orthogonal neighbours of `pos` inside the grid.
+    pub fn neighbours4(&self, pos: Point<usize>) -> impl Iterator<Item = Point<usize>> + '_ {
+        NEIGHBOURS_4
+            .into_iter()
+            .filter_map(move |d| pos.offset(d))
+            .filter(|p| self.contains(*p))
+    }
+
+    /// Orthogonal and diagonal neighbours of `pos` inside the grid.
+    pub fn neighbours8(&self, pos: Point<usize>) -> impl Iterator<Item = Point<usize>> + '_ {
+        NEIGHBOURS_8
+            .into_iter()
+            .filter_map(move |d| pos.offset(d))
+            .filter(|p| self.contains(*p))
+    }
+
+    /// Cells from `pos` towards the edge in `direction`, nearest first, `pos` excluded.
+    pub fn ray(
+        &self,
+        pos: Point<usize>,
+        direction: Direction,
+    ) -> impl Iterator<Item = (Point<usize>, &T)> + '_ {
+        iter::successors(self.step(pos, direction), move |p| self.step(*p, direction))
+            .map(|p| (p, &self[p]))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        // `chunks_exact` refuses a zero width
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(x < self.width, "column {x} out of {}", self.width);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// Every position in reading order.
+    pub fn positions(&self) -> impl Iterator<Item = Point<usize>> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    /// Every cell with its position, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point<usize>, &T)> + '_ {
+        self.positions().zip(&self.cells)
+    }
+
+    /// Grid of the same shape with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Show the grid with one character per cell, `cell` picks the character.
+    pub fn display<F: Fn(&T) -> char>(&self, cell: F) -> GridDisplay<'_, T, F> {
+        GridDisplay { grid: self, cell }
+    }
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Self::from_cells(0, vec![])
+    }
+}
+
+impl<T> Index<Point<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point<usize>) -> &T {
+        match self.get(pos) {
+            Some(cell) => cell,
+            None => panic!("{pos:?} is outside the {}x{} grid", self.width, self.height),
+        }
+    }
+}
+
+impl<T> IndexMut<Point<usize>> for Grid<T> {
+    fn index_mut(&mut self, pos: Point<usize>) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(pos) {
+            Some(cell) => cell,
+            None => panic!("{pos:?} is outside the {width}x{height} grid"),
+        }
+    }
+}
+
+/// [`fmt::Display`] of a [`Grid`], see [`Grid::display`].
+pub struct GridDisplay<'a, T, F> {
+    grid: &'a Grid<T>,
+    cell: F,
+}
+
+impl<T, F: Fn(&T) -> char> fmt::Display for GridDisplay<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.grid.rows() {
+            for c in row {
+                write!(f, "{}", (self.cell)(c))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits() -> Grid<u8> {
+        Grid::parse(0, "\n  123\n  456\n", "a digit", |_, c| {
+            Some(c.to_digit(10)? as u8)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point::new(2, 1)], 6);
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(
+            grid.display(|d| char::from(b'0' + d)).to_string(),
+            "123\n456\n"
+        );
+        let error = Grid::parse(0, "12\n3x", "a digit", |_, c| c.to_digit(10)).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(2)));
+        assert_eq!(error.found, "x");
+        let error = Grid::parse(0, "12\n  3\n", "a digit", |_, c| c.to_digit(10)).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(3)));
+        assert_eq!(error.expected, "a row of 2 cells");
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut grid = digits();
+        assert_eq!(grid.row(1), [4, 5, 6]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), [2, 5]);
+        let ray: Vec<_> = grid.ray(Point::new(2, 0), Direction::Left).collect();
+        assert_eq!(ray, [(Point::new(1, 0), &2), (Point::new(0, 0), &1)]);
+        assert_eq!(grid.ray(Point::new(0, 1), Direction::Down).count(), 0);
+        assert_eq!(grid.neighbours4(Point::new(0, 0)).count(), 2);
+        assert_eq!(grid.neighbours8(Point::new(1, 0)).count(), 5);
+        assert_eq!(
+            grid.step(Point::new(1, 1), Direction::Up),
+            Some(Point::new(1, 0))
+        );
+        assert_eq!(grid.iter().nth(4), Some((Point::new(1, 1), &5)));
+
+        *grid.get_mut(Point::new(0, 0)).unwrap() = 9;
+        assert_eq!(grid.map(|d| u32::from(*d) * 2).row(0), [18, 4, 6]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    borrow::Cow,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+/// Directory holding one `dayNN` folder per puzzle.
+pub const INPUT_DIR: &str = "input";
+
+/// Where a puzzle input can be read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// `input/dayNN/input`.
+    Default,
+    Path(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    /// `-` means stdin, anything else is a file path.
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            Self::Stdin
+        } else {
+            Self::Path(arg.into())
+        }
+    }
+}
+
+/// Folder of the given day, e.g. `input/day07`.
+pub fn day_dir(day: u32) -> PathBuf {
+    Path::new(INPUT_DIR).join(format!("day{day:02}"))
+}
+
+/// Puzzle input used when none is given, e.g. `input/day07/input`.
+pub fn default_path(day: u32) -> PathBuf {
+    day_dir(day).join("input")
+}
+
+/// Read the puzzle input of `day` from `source`.
+pub fn load(day: u32, source: &Source) -> Result<String> {
+    match source {
+        Source::Default => read_file(day, &default_path(day)),
+        Source::Path(path) => read_file(day, path),
+        Source::Stdin => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .with_context(|| format!("failed to read input for day {day} from stdin"))?;
+            Ok(buf)
+        }
+    }
+}
+
+fn read_file(day: u32, path: &Path) -> Result<String> {
+    fs::read_to_string(path)
+        .with_context(|| format!("failed to read input for day {day} from {}", path.display()))
+}
+
+/// Bring an input to the shape every parser expects: LF line endings, no byte order mark and
+/// no whitespace at the end of a line. Leading spaces are kept, day 05 draws its crates with
+/// them.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let clean = !input.contains('\r') && input.split('\n').all(|l| l.trim_end() == l);
+    if clean {
+        return Cow::Borrowed(input);
+    }
+    let mut normalized = String::with_capacity(input.len());
+    for (i, line) in input.replace("\r\n", "\n").split(['\n', '\r']).enumerate() {
+        if i > 0 {
+            normalized.push('\n');
+        }
+        normalized.push_str(line.trim_end());
+    }
+    Cow::Owned(normalized)
+}
+
+/// `input` with Windows line endings, to check a day copes with them.
+#[cfg(test)]
+pub fn crlf(input: &str) -> String {
+    input.replace('\n', "\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_path() {
+        assert_eq!(default_path(7), Path::new("input/day07/input"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let clean = "    [D]\n[N] [C]\n\n1\n";
+        assert!(matches!(normalize(clean), Cow::Borrowed(s) if s == clean));
+        assert_eq!(normalize(&crlf(clean)), clean);
+        assert_eq!(normalize("\u{feff}a \t\r\n \r\nb\rc  "), "a\n\nb\nc");
+        assert!(matches!(normalize("\u{feff}x"), Cow::Borrowed("x")));
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let source = Source::from_arg("input/day99/missing");
+        let err = load(99, &source).unwrap_err();
+        assert!(err.to_string().contains("input/day99/missing"));
+    }
+}
@@ -0,0 +1,307 @@
+use std::{ops::RangeInclusive, slice};
+
+/// Set of integers stored as sorted, disjoint inclusive ranges.
+///
+/// Touching ranges are merged, so `1..=3` and `4..=6` are kept as `1..=6`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    /// `(start, end)` with `start <= end`, sorted, with a gap of at least one between two.
+    ranges: Vec<(i64, i64)>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add every value of `range`, an empty range is ignored.
+    pub fn insert(&mut self, range: RangeInclusive<i64>) {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        // ranges overlapping or touching the new one
+        let lo = self
+            .ranges
+            .partition_point(|r| r.1.saturating_add(1) < start);
+        let hi = self
+            .ranges
+            .partition_point(|r| r.0 <= end.saturating_add(1));
+        if lo < hi {
+            start = start.min(self.ranges[lo].0);
+            end = end.max(self.ranges[hi - 1].1);
+        }
+        self.ranges.splice(lo..hi, [(start, end)]);
+    }
+
+    /// Take out every value of `range`, an empty range is ignored.
+    pub fn remove(&mut self, range: RangeInclusive<i64>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        let lo = self.ranges.partition_point(|r| r.1 < start);
+        let hi = self.ranges.partition_point(|r| r.0 <= end);
+        if lo == hi {
+            return;
+        }
+        let first = self.ranges[lo];
+        let last = self.ranges[hi - 1];
+        let left = (first.0 < start).then(|| (first.0, start - 1));
+        let right = (last.1 > end).then(|| (end + 1, last.1));
+        self.ranges.splice(lo..hi, left.into_iter().chain(right));
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.contains_range(value..=value)
+    }
+
+    /// Whether every value of `range` is in the set, always true for an empty range.
+    pub fn contains_range(&self, range: RangeInclusive<i64>) -> bool {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return true;
+        }
+        let idx = self.ranges.partition_point(|r| r.1 < start);
+        self.ranges
+            .get(idx)
+            .is_some_and(|r| r.0 <= start && end <= r.1)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        result.extend(other.ranges());
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(&&x), Some(&&y)) = (a.peek(), b.peek()) {
+            let start = x.0.max(y.0);
+            let end = x.1.min(y.1);
+            if start <= end {
+                ranges.push((start, end));
+            }
+            // drop whichever ends first, it cannot meet anything further in the other set
+            if x.1 < y.1 {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in other.ranges() {
+            result.remove(range);
+        }
+        result
+    }
+
+    /// Number of values in the set.
+    ///
+    /// Overflows for a set covering every `i64`.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|r| r.1.abs_diff(r.0) + 1).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The disjoint ranges making up the set, in increasing order.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<i64>> + '_ {
+        self.ranges.iter().map(|&(start, end)| start..=end)
+    }
+
+    /// Ranges missing between the lowest and highest values of the set.
+    pub fn gaps(&self) -> Gaps<'_> {
+        match (self.ranges.first(), self.ranges.last()) {
+            (Some(first), Some(last)) => self.gaps_within(first.0..=last.1),
+            _ => Gaps::default(),
+        }
+    }
+
+    /// Ranges of `bounds` missing from the set. Use `.flatten()` to walk the values.
+    pub fn gaps_within(&self, bounds: RangeInclusive<i64>) -> Gaps<'_> {
+        let (start, end) = bounds.into_inner();
+        let skip = self.ranges.partition_point(|r| r.1 < start);
+        Gaps {
+            ranges: self.ranges[skip..].iter(),
+            next: (start <= end).then_some(start),
+            end,
+        }
+    }
+}
+
+impl Extend<RangeInclusive<i64>> for IntervalSet {
+    fn extend<I: IntoIterator<Item = RangeInclusive<i64>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl FromIterator<RangeInclusive<i64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<i64>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl From<RangeInclusive<i64>> for IntervalSet {
+    fn from(range: RangeInclusive<i64>) -> Self {
+        Self::from_iter([range])
+    }
+}
+
+/// Iterator over the missing ranges of an [`IntervalSet`], see [`IntervalSet::gaps_within`].
+#[derive(Debug, Clone, Default)]
+pub struct Gaps<'a> {
+    ranges: slice::Iter<'a, (i64, i64)>,
+    /// Lowest value not yet known to be covered, `None` once past the end.
+    next: Option<i64>,
+    end: i64,
+}
+
+impl Iterator for Gaps<'_> {
+    type Item = RangeInclusive<i64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let from = self.next.filter(|from| *from <= self.end)?;
+            match self.ranges.next() {
+                Some(&(start, end)) => {
+                    self.next = end.checked_add(1);
+                    if start > from {
+                        return Some(from..=(start - 1).min(self.end));
+                    }
+                }
+                None => {
+                    self.next = None;
+                    return Some(from..=self.end);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    /// xorshift64, enough to shuffle test cases without a dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Range inside `0..64`, sometimes empty.
+        fn range(&mut self) -> RangeInclusive<i64> {
+            let start = (self.next() % 64) as i64;
+            let len = (self.next() % 12) as i64;
+            start..=start + len - 2
+        }
+    }
+
+    fn model(set: &IntervalSet) -> BTreeSet<i64> {
+        set.ranges().flatten().collect()
+    }
+
+    fn random_pair(rng: &mut Rng) -> (IntervalSet, BTreeSet<i64>) {
+        let mut set = IntervalSet::new();
+        let mut values = BTreeSet::new();
+        for _ in 0..rng.next() % 8 {
+            let range = rng.range();
+            if rng.next().is_multiple_of(3) {
+                set.remove(range.clone());
+                for v in range {
+                    values.remove(&v);
+                }
+            } else {
+                set.insert(range.clone());
+                values.extend(range);
+            }
+        }
+        (set, values)
+    }
+
+    fn assert_canonical(set: &IntervalSet) {
+        for r in &set.ranges {
+            assert!(r.0 <= r.1, "{set:?}");
+        }
+        for w in set.ranges.windows(2) {
+            assert!(w[0].1 + 1 < w[1].0, "{set:?}");
+        }
+    }
+
+    #[test]
+    fn test_against_model() {
+        let mut rng = Rng(0x2022_1215);
+        for _ in 0..2000 {
+            let (a, model_a) = random_pair(&mut rng);
+            let (b, model_b) = random_pair(&mut rng);
+            assert_canonical(&a);
+            assert_eq!(model(&a), model_a);
+            assert_eq!(a.len(), model_a.len() as u64);
+            assert_eq!(a.is_empty(), model_a.is_empty());
+
+            let union = a.union(&b);
+            let intersection = a.intersection(&b);
+            let difference = a.difference(&b);
+            for set in [&union, &intersection, &difference] {
+                assert_canonical(set);
+            }
+            assert_eq!(model(&union), &model_a | &model_b);
+            assert_eq!(model(&intersection), &model_a & &model_b);
+            assert_eq!(model(&difference), &model_a - &model_b);
+
+            let range = rng.range();
+            let all = range.clone().all(|v| model_a.contains(&v));
+            assert_eq!(a.contains_range(range), all);
+            let value = (rng.next() % 70) as i64;
+            assert_eq!(a.contains(value), model_a.contains(&value));
+
+            let bounds = rng.range();
+            let missing: Vec<i64> = bounds.clone().filter(|v| !model_a.contains(v)).collect();
+            assert_eq!(a.gaps_within(bounds).flatten().collect::<Vec<_>>(), missing);
+            let (lo, hi) = (model_a.first(), model_a.last());
+            let inner: Vec<i64> = match (lo, hi) {
+                (Some(&lo), Some(&hi)) => (lo..=hi).filter(|v| !model_a.contains(v)).collect(),
+                _ => vec![],
+            };
+            assert_eq!(a.gaps().flatten().collect::<Vec<_>>(), inner);
+        }
+    }
+
+    #[test]
+    fn test_edges() {
+        let mut set = IntervalSet::from(1..=3);
+        set.insert(4..=6);
+        set.insert(i64::MAX - 1..=i64::MAX);
+        assert_eq!(
+            set.ranges().collect::<Vec<_>>(),
+            [1..=6, i64::MAX - 1..=i64::MAX]
+        );
+        set.remove(i64::MIN..=2);
+        assert_eq!(set.ranges().next(), Some(3..=6));
+        assert_eq!(set.gaps_within(0..=8).collect::<Vec<_>>(), [0..=2, 7..=8]);
+        assert_eq!(set.gaps_within(i64::MAX - 2..=i64::MAX).count(), 1);
+        let (start, end) = (5, 4);
+        assert!(set.contains_range(start..=end));
+    }
+}
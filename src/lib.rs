@@ -1,3 +1,14 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+
+pub mod alloc;
+pub mod animate;
+pub mod bench;
+pub mod client;
+pub mod combinator;
+pub mod cycle;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -15,17 +26,299 @@ pub mod day14;
 pub mod day15;
 pub mod day16;
 pub mod day17;
-pub mod day18;
-pub mod day19;
-pub mod day20;
-pub mod day21;
-pub mod day22;
-pub mod day23;
-pub mod day24;
-pub mod day25;
-pub mod day26;
-pub mod day27;
-pub mod day28;
-pub mod day29;
-pub mod day30;
-pub mod day31;
+pub mod fetch;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod interval;
+pub mod parse;
+pub mod render;
+pub mod runner;
+pub mod scaffold;
+pub mod search;
+pub mod submit;
+pub mod verify;
+pub mod watch;
+
+/// A solution for one day of the calendar.
+pub trait Solver {
+    /// Day of December the puzzle belongs to.
+    const DAY: u32;
+
+    fn part1(&self, input: &str) -> Answer;
+    fn part2(&self, input: &str) -> Answer;
+}
+
+/// One of the two halves of a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(&self) -> u8 {
+        match self {
+            Self::One => 1,
+            Self::Two => 2,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "part {}", self.number())
+    }
+}
+
+impl FromStr for Part {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Self::One),
+            "2" => Ok(Self::Two),
+            _ => bail!("invalid part `{s}`, expected 1 or 2"),
+        }
+    }
+}
+
+/// Answer of one part, keeping track of what kind of value it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Integer(i64),
+    Text(String),
+    /// A drawing spanning several lines, e.g. letters printed on a screen.
+    Grid(String),
+}
+
+impl Answer {
+    /// Name of the kind of answer, as reported in JSON output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Integer(_) => "integer",
+            Self::Text(_) => "string",
+            Self::Grid(_) => "grid",
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(n) => write!(f, "{n}"),
+            Self::Text(s) => write!(f, "{s}"),
+            Self::Grid(s) => write!(f, "{}", s.trim_end()),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Self::Integer(n as i64)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(n: isize) -> Self {
+        Self::Integer(n as i64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Self::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Self::Text(s.to_string())
+    }
+}
+
+impl PartialEq<i64> for Answer {
+    fn eq(&self, other: &i64) -> bool {
+        matches!(self, Self::Integer(n) if n == other)
+    }
+}
+
+/// Text and grid answers compare equal to their printed form.
+impl PartialEq<&str> for Answer {
+    fn eq(&self, other: &&str) -> bool {
+        match self {
+            Self::Integer(_) => false,
+            Self::Text(s) => s == other,
+            Self::Grid(s) => s.trim_end() == other.trim_end(),
+        }
+    }
+}
+
+/// Object safe view of a [`Solver`], so that every day can live in the same registry.
+///
+/// Inputs go through [`input::normalize`] on the way, so a day never sees CRLF line endings or
+/// trailing whitespace. Benchmarks count that as parsing.
+pub trait Puzzle: Sync {
+    fn day(&self) -> u32;
+    fn run_part1(&self, input: &str) -> Answer;
+    fn run_part2(&self, input: &str) -> Answer;
+
+    fn run(&self, part: Part, input: &str) -> Answer {
+        match part {
+            Part::One => self.run_part1(input),
+            Part::Two => self.run_part2(input),
+        }
+    }
+}
+
+impl<S: Solver + Sync> Puzzle for S {
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn run_part1(&self, input: &str) -> Answer {
+        self.part1(&bench::parsing(|| input::normalize(input)))
+    }
+
+    fn run_part2(&self, input: &str) -> Answer {
+        self.part2(&bench::parsing(|| input::normalize(input)))
+    }
+}
+
+/// Every implemented day, in calendar order.
+///
+/// Adding a day only needs a new entry here, the binary picks it up from this list.
+pub static SOLVERS: &[&dyn Puzzle] = &[
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+];
+
+/// Look up the solver registered for `day`.
+pub fn solver(day: u32) -> Option<&'static dyn Puzzle> {
+    SOLVERS.iter().copied().find(|s| s.day() == day)
+}
+
+/// Days that have a registered solver.
+pub fn available_days() -> Vec<u32> {
+    SOLVERS.iter().map(|s| s.day()).collect()
+}
+
+/// Picture of the puzzle of `day`, for the days that draw one.
+pub fn picture(day: u32, input: &str) -> Option<Box<dyn render::Render>> {
+    let input = input::normalize(input);
+    let picture: Box<dyn render::Render> = match day {
+        8 => Box::new(day08::picture(&input)),
+        12 => Box::new(day12::picture(&input)),
+        14 => Box::new(day14::picture(&input)),
+        15 => Box::new(day15::picture(&input)),
+        17 => Box::new(day17::picture(&input)),
+        _ => return None,
+    };
+    Some(picture)
+}
+
+/// Record the simulation of `day` into `recorder`, for the days that animate one.
+pub fn animation(day: u32, input: &str, recorder: &mut animate::Recorder) -> Result<()> {
+    let input = input::normalize(input);
+    match day {
+        9 => day09::animate(&input, recorder),
+        14 => day14::animate(&input, recorder),
+        17 => day17::animate(&input, recorder),
+        _ => bail!("day {day} has no animation, animate runs days 9, 14 and 17"),
+    }
+    Ok(())
+}
+
+/// Offset of the timezone puzzles unlock in, midnight UTC-5.
+const RELEASE_OFFSET_SECS: i32 = -5 * 3600;
+
+/// Day to run when none is given: the latest implemented puzzle released by `now`.
+///
+/// Only days of December count, the rest of the year there is no sensible default.
+pub fn default_day(now: DateTime<Utc>) -> Result<u32> {
+    let offset = FixedOffset::east_opt(RELEASE_OFFSET_SECS).expect("valid offset");
+    let today = now.with_timezone(&offset).date_naive();
+    if today.month() != 12 {
+        bail!("no puzzle is released on {today} (UTC-5), pass the DAY to run");
+    }
+    match available_days()
+        .into_iter()
+        .filter(|&d| d <= today.day())
+        .max()
+    {
+        Some(day) => Ok(day),
+        None => bail!("no implemented day is released yet on {today} (UTC-5), pass the DAY to run"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Answer::from(42usize), Answer::Integer(42));
+        assert_eq!(Answer::from(-3isize), -3);
+        assert_eq!(Answer::from("CMZ"), "CMZ");
+        assert_ne!(Answer::Integer(1), "1");
+        assert_eq!(Answer::Grid("#.\n.#\n".to_string()), "#.\n.#");
+        assert_eq!(Answer::Grid("#.\n.#\n".to_string()).to_string(), "#.\n.#");
+        assert_eq!(Answer::Integer(-3).to_string(), "-3");
+    }
+
+    #[test]
+    fn test_solver_answers() {
+        let answer = solver(1).unwrap().run(Part::One, "1000\n2000\n\n4000\n");
+        assert_eq!(answer, 4000);
+        assert_eq!(answer.kind(), "integer");
+        let answer = solver(10).unwrap().run(Part::Two, "noop\n");
+        assert_eq!(answer.kind(), "grid");
+    }
+
+    #[test]
+    fn test_picture() {
+        let forest = picture(8, "30373\r\n25512\r\n").unwrap();
+        assert_eq!(forest.size(), (5, 2));
+        assert!(picture(1, "").is_none());
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut recorder = animate::Recorder::default();
+        animation(9, "R 4\r\nU 4\r\n", &mut recorder).unwrap();
+        // every step of the head, then the final state
+        assert_eq!(recorder.seen(), 8);
+        assert_eq!(recorder.len(), 9);
+        assert!(animation(8, "", &mut recorder).is_err());
+    }
+
+    #[test]
+    fn test_default_day() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(default_day(at("2022-12-05T05:00:00Z")).unwrap(), 5);
+        // still the evening of the 4th where puzzles are released
+        assert_eq!(default_day(at("2022-12-05T04:59:59Z")).unwrap(), 4);
+        let latest = *available_days().last().unwrap();
+        assert_eq!(default_day(at("2022-12-31T12:00:00Z")).unwrap(), latest);
+        assert!(default_day(at("2022-12-01T04:00:00Z")).is_err());
+        assert!(default_day(at("2023-03-30T12:00:00Z")).is_err());
+        assert_eq!(default_day(at("2023-01-01T04:00:00Z")).unwrap(), latest);
+    }
+}
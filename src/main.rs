@@ -1,50 +1,411 @@
-use anyhow::Result;
-use aoc_2022::*;
-use chrono::prelude::*;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-fn main() -> Result<()> {
-    let utc: DateTime<Utc> = Utc::now();
-    let day = if let Some(s) = std::env::args().nth(1) {
-        s.parse().unwrap_or_else(|_| utc.day())
+use anyhow::{Context, Result, bail};
+use aoc_2022::{input::Source, *};
+use chrono::Utc;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOC: alloc::CountingAlloc = alloc::CountingAlloc;
+
+const USAGE: &str = "usage: aoc_2022 [COMMAND] [DAY] [OPTIONS]
+
+commands:
+  list                  list implemented days
+  all                   run every implemented day and print a summary table
+  verify                compare answers with input/dayNN/answers, every day if DAY is omitted
+  record                store the current answers in input/dayNN/answers
+  watch                 run DAY again whenever a file in input/dayNN/ (or --input) changes,
+                        showing how the answers changed
+  fetch                 download the input of DAY to input/dayNN/input unless it is there,
+                        using the session token in $AOC_SESSION or .session ($AOC_SESSION_FILE),
+                        from $AOC_BASE_URL if set
+  submit                run one part of DAY (--part is required) and submit its answer,
+                        keeping the verdicts in input/dayNN/submissions so that a known wrong
+                        answer is never sent twice, correct ones are recorded in answers
+  new                   generate src/dayNN.rs and input/dayNN/example, and register DAY
+  bench                 time repeated runs of DAY, reporting parsing and solving separately
+  render                draw DAY as a .png or .ppm picture, for days 8, 12, 14, 15 and 17
+  animate               record the simulation of DAY as a GIF, or as .ppm frames in a
+                        directory when --output does not end in .gif, for days 9, 14 and 17
+
+options:
+  DAY                   day to run, defaults to the latest implemented puzzle released
+                        in December (puzzles unlock at midnight UTC-5)
+  -i, --input PATH      read the puzzle input from PATH, `-` for stdin
+                        (default: input/dayNN/input)
+  -p, --part PART       only run part 1 or part 2 (default: both)
+  -n, --iterations N    timed runs per part for bench (default: 10)
+  --warmup N            untimed runs per part before timing for bench (default: 3)
+  -j, --jobs N          run the days and parts of all on N threads,
+                        0 for one per core (default: 1)
+  -o, --output PATH     where render and animate write (default: dayNN.png or dayNN.gif)
+  --scale N             pixels across each cell for render and animate
+                        (default: 4 for render, 2 for animate)
+  --every N             keep one step out of every N for animate
+                        (default: enough for about 300 frames)
+  --delay MS            milliseconds each frame of a GIF is shown for (default: 40)
+  --json                print one JSON object per day and part instead of text,
+                        for running a day, all and record";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Run,
+    List,
+    All,
+    Verify,
+    Record,
+    Bench,
+    New,
+    Watch,
+    Fetch,
+    Submit,
+    Render,
+    Animate,
+}
+
+#[derive(Debug)]
+struct Args {
+    mode: Mode,
+    day: Option<u32>,
+    input: Source,
+    parts: Vec<Part>,
+    iterations: usize,
+    warmup: usize,
+    json: bool,
+    jobs: usize,
+    output: Option<PathBuf>,
+    scale: Option<usize>,
+    every: Option<usize>,
+    delay: Duration,
+}
+
+fn parse_parts(s: &str) -> Result<Vec<Part>> {
+    if s == "both" {
+        Ok(Part::ALL.to_vec())
+    } else {
+        Ok(vec![s.parse()?])
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut parsed = Args {
+        mode: Mode::Run,
+        day: None,
+        input: Source::Default,
+        parts: Part::ALL.to_vec(),
+        iterations: 10,
+        warmup: 3,
+        json: false,
+        jobs: 1,
+        output: None,
+        scale: None,
+        every: None,
+        delay: Duration::from_millis(40),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "list" => parsed.mode = Mode::List,
+            "all" => parsed.mode = Mode::All,
+            "verify" => parsed.mode = Mode::Verify,
+            "record" => parsed.mode = Mode::Record,
+            "bench" => parsed.mode = Mode::Bench,
+            "new" => parsed.mode = Mode::New,
+            "watch" => parsed.mode = Mode::Watch,
+            "fetch" => parsed.mode = Mode::Fetch,
+            "submit" => parsed.mode = Mode::Submit,
+            "render" => parsed.mode = Mode::Render,
+            "animate" => parsed.mode = Mode::Animate,
+            "-i" | "--input" => {
+                let path = args.next().context("missing value for --input")?;
+                parsed.input = Source::from_arg(&path);
+            }
+            "-p" | "--part" => {
+                let part = args.next().context("missing value for --part")?;
+                parsed.parts = parse_parts(&part)?;
+            }
+            "-n" | "--iterations" => {
+                let n = args.next().context("missing value for --iterations")?;
+                parsed.iterations = n.parse().context("invalid value for --iterations")?;
+            }
+            "--warmup" => {
+                let n = args.next().context("missing value for --warmup")?;
+                parsed.warmup = n.parse().context("invalid value for --warmup")?;
+            }
+            "-j" | "--jobs" => {
+                let n = args.next().context("missing value for --jobs")?;
+                parsed.jobs = n.parse().context("invalid value for --jobs")?;
+            }
+            "-o" | "--output" => {
+                let path = args.next().context("missing value for --output")?;
+                parsed.output = Some(PathBuf::from(path));
+            }
+            "--scale" => {
+                let n = args.next().context("missing value for --scale")?;
+                parsed.scale = Some(n.parse().context("invalid value for --scale")?);
+            }
+            "--every" => {
+                let n = args.next().context("missing value for --every")?;
+                let every = n.parse().context("invalid value for --every")?;
+                if every == 0 {
+                    bail!("--every needs at least 1");
+                }
+                parsed.every = Some(every);
+            }
+            "--delay" => {
+                let ms = args.next().context("missing value for --delay")?;
+                let ms = ms.parse().context("invalid value for --delay")?;
+                parsed.delay = Duration::from_millis(ms);
+            }
+            "--json" => parsed.json = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            _ => {
+                let day = arg.parse().with_context(|| {
+                    format!("invalid argument `{arg}`, expected a DAY\n\n{USAGE}")
+                })?;
+                parsed.day = Some(day);
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+fn print_available_days() {
+    let days: Vec<String> = available_days().iter().map(|d| d.to_string()).collect();
+    println!("available days: {}", days.join(", "));
+}
+
+fn lookup(day: u32) -> Result<&'static dyn Puzzle> {
+    let Some(puzzle) = solver(day) else {
+        print_available_days();
+        bail!("day {day} is not implemented");
+    };
+    Ok(puzzle)
+}
+
+/// The requested day, or every day when none is given.
+fn selected(day: Option<u32>) -> Result<Vec<&'static dyn Puzzle>> {
+    match day {
+        Some(day) => Ok(vec![lookup(day)?]),
+        None => Ok(SOLVERS.to_vec()),
+    }
+}
+
+fn run(day: u32, source: &Source, parts: &[Part], json: bool) -> Result<()> {
+    let puzzle = lookup(day)?;
+    if json {
+        runner::print_json(&runner::quietly(|| runner::run_day(puzzle, source, parts)));
+        return Ok(());
+    }
+    let input = input::load(day, source)?;
+    for &part in parts {
+        let answer = puzzle.run(part, &input);
+        if let Answer::Grid(_) = answer {
+            println!("day {day:02} {part}:\n{answer}");
+        } else {
+            println!("day {day:02} {part}: {answer}");
+        }
+    }
+    Ok(())
+}
+
+fn bench(
+    day: u32,
+    source: &Source,
+    parts: &[Part],
+    warmup: usize,
+    iterations: usize,
+) -> Result<()> {
+    let puzzle = lookup(day)?;
+    let input = input::load(day, source)?;
+    for &part in parts {
+        // a part that panics can not be timed
+        let outcome = runner::quietly(|| runner::run_part(puzzle, part, &input));
+        if let Err(e) = &outcome.answer {
+            println!("day {day:02} {part}: FAILED: {e}\n");
+            continue;
+        }
+        print!("{}", bench::bench(puzzle, part, &input, warmup, iterations));
+        if let Some(alloc) = outcome.alloc {
+            println!("memory {alloc}");
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn submit(day: u32, source: &Source, parts: &[Part]) -> Result<()> {
+    let &[part] = parts else {
+        bail!("submit needs the part to submit, pass --part 1 or --part 2");
+    };
+    let puzzle = lookup(day)?;
+    let input = input::load(day, source)?;
+    let outcome = runner::quietly(|| runner::run_part(puzzle, part, &input));
+    let answer = match outcome.answer {
+        Ok(answer) => answer,
+        Err(e) => bail!("day {day} {part} failed: {e}"),
+    };
+    println!("day {day:02} {part}: submitting {answer}");
+    let root = Path::new(".");
+    let client = || client::Client::from_env(root);
+    let verdict = submit::submit(client, root, day, part, &answer)?;
+    println!("day {day:02} {part}: {verdict}");
+    Ok(())
+}
+
+/// Largest picture render writes, so a big `--scale` fails before allocating it.
+const MAX_PIXELS: usize = 1 << 26;
+
+fn render(day: u32, source: &Source, output: Option<&Path>, scale: usize) -> Result<()> {
+    let input = input::load(day, source)?;
+    let Some(picture) = picture(day, &input) else {
+        bail!("day {day} has no picture, render draws days 8, 12, 14, 15 and 17");
+    };
+    let (width, height) = picture.size();
+    let (width, height) = (width.saturating_mul(scale), height.saturating_mul(scale));
+    if width.saturating_mul(height) > MAX_PIXELS {
+        bail!("the picture of day {day} would be {width}x{height} pixels, too large to write");
+    }
+    let path = output.map_or_else(
+        || PathBuf::from(format!("day{day:02}.png")),
+        Path::to_path_buf,
+    );
+    picture
+        .save(&path, scale)
+        .with_context(|| format!("could not write {}", path.display()))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+/// Frames animate keeps when --every is not given.
+const DEFAULT_FRAMES: usize = 300;
+
+fn animate(
+    day: u32,
+    source: &Source,
+    output: Option<&Path>,
+    scale: usize,
+    every: Option<usize>,
+    delay: Duration,
+) -> Result<()> {
+    let input = input::load(day, source)?;
+    let every = match every {
+        Some(every) => every,
+        None => {
+            // the real inputs take tens of thousands of steps, too many frames to keep
+            let mut counter = animate::Recorder::default().with_frame_skip(usize::MAX);
+            animation(day, &input, &mut counter)?;
+            counter.seen().div_ceil(DEFAULT_FRAMES).max(1)
+        }
+    };
+    let mut recorder = animate::Recorder::default().with_frame_skip(every);
+    animation(day, &input, &mut recorder)?;
+    let path = output.map_or_else(
+        || PathBuf::from(format!("day{day:02}.gif")),
+        Path::to_path_buf,
+    );
+    let saved = if path.extension().is_some_and(|e| e == "gif") {
+        recorder.save_gif(&path, scale, delay)
     } else {
-        utc.day()
-    };
-
-    let mod_name = format!("day{day:02}");
-    match mod_name.as_str() {
-        "day01" => day01::run(),
-        "day02" => day02::run(),
-        "day03" => day03::run(),
-        "day04" => day04::run(),
-        "day05" => day05::run(),
-        "day06" => day06::run(),
-        "day07" => day07::run(),
-        "day08" => day08::run(),
-        "day09" => day09::run(),
-        "day10" => day10::run(),
-        "day11" => day11::run(),
-        "day12" => day12::run(),
-        "day13" => day13::run(),
-        "day14" => day14::run(),
-        "day15" => day15::run(),
-        "day16" => day16::run(),
-        "day17" => day17::run(),
-        "day18" => day18::run(),
-        "day19" => day19::run(),
-        "day20" => day20::run(),
-        "day21" => day21::run(),
-        "day22" => day22::run(),
-        "day23" => day23::run(),
-        "day24" => day24::run(),
-        "day25" => day25::run(),
-        "day26" => day26::run(),
-        "day27" => day27::run(),
-        "day28" => day28::run(),
-        "day29" => day29::run(),
-        "day30" => day30::run(),
-        "day31" => day31::run(),
-        _ => unreachable!(),
+        recorder.save_ppm_sequence(&path, scale)
+    };
+    saved.with_context(|| format!("could not write {}", path.display()))?;
+    println!("wrote {} frames to {}", recorder.len(), path.display());
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = parse_args(std::env::args().skip(1))?;
+    if args.json && !matches!(args.mode, Mode::Run | Mode::All | Mode::Record) {
+        bail!("--json is only supported when running days, with all or with record");
+    }
+    let print_outcomes = |outcomes: &[runner::Outcome], wall| {
+        if args.json {
+            runner::print_json(outcomes)
+        } else {
+            runner::print_table(outcomes, wall)
+        }
     };
+    match args.mode {
+        Mode::List => print_available_days(),
+        Mode::All => {
+            let start = Instant::now();
+            let outcomes = runner::run_all(&args.parts, args.jobs);
+            print_outcomes(&outcomes, Some(start.elapsed()));
+        }
+        Mode::Verify => {
+            let mut checks = vec![];
+            for puzzle in selected(args.day)? {
+                checks.extend(verify::verify(puzzle, &args.input, &args.parts)?);
+            }
+            verify::print_report(&checks);
+            if checks
+                .iter()
+                .any(|c| matches!(c.status, verify::Status::Fail(_)))
+            {
+                bail!("some answers did not match");
+            }
+        }
+        Mode::Record => {
+            let mut outcomes = vec![];
+            for puzzle in selected(args.day)? {
+                outcomes.extend(verify::record(puzzle, &args.input, &args.parts)?);
+            }
+            print_outcomes(&outcomes, None);
+        }
+        Mode::New => {
+            let day = args.day.context("new needs the DAY to generate")?;
+            for path in scaffold::scaffold(Path::new("."), day)? {
+                println!("wrote {}", path.display());
+            }
+        }
+        Mode::Fetch => {
+            let day = args.day.map_or_else(|| default_day(Utc::now()), Ok)?;
+            let root = Path::new(".");
+            match fetch::fetch(|| client::Client::from_env(root), root, day)? {
+                fetch::Fetched::Downloaded(path) => println!("downloaded {}", path.display()),
+                fetch::Fetched::Cached(path) => println!("already have {}", path.display()),
+            }
+        }
+        Mode::Submit => {
+            let day = args.day.map_or_else(|| default_day(Utc::now()), Ok)?;
+            submit(day, &args.input, &args.parts)?;
+        }
+        Mode::Watch => {
+            let day = args.day.map_or_else(|| default_day(Utc::now()), Ok)?;
+            watch::watch(lookup(day)?, &args.input, &args.parts)?;
+        }
+        Mode::Bench => {
+            let day = args.day.map_or_else(|| default_day(Utc::now()), Ok)?;
+            bench(day, &args.input, &args.parts, args.warmup, args.iterations)?;
+        }
+        Mode::Render => {
+            let day = args.day.map_or_else(|| default_day(Utc::now()), Ok)?;
+            let scale = args.scale.unwrap_or(4);
+            render(day, &args.input, args.output.as_deref(), scale)?;
+        }
+        Mode::Animate => {
+            let day = args.day.map_or_else(|| default_day(Utc::now()), Ok)?;
+            animate(
+                day,
+                &args.input,
+                args.output.as_deref(),
+                args.scale.unwrap_or(2),
+                args.every,
+                args.delay,
+            )?;
+        }
+        Mode::Run => {
+            let day = args.day.map_or_else(|| default_day(Utc::now()), Ok)?;
+            run(day, &args.input, &args.parts, args.json)?;
+        }
+    }
 
     Ok(())
 }
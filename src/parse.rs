@@ -0,0 +1,167 @@
+use std::{error::Error, fmt, str::FromStr};
+
+/// An error in a puzzle input, with where it is and what was expected there.
+///
+/// Errors are built about the text a parser was given, then moved by [`ParseError::rebase`]
+/// as they bubble up through the parsers of the enclosing text, so the line and column in the
+/// end point into the whole input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u32,
+    /// Line of the error, counting from 1.
+    pub line: Option<usize>,
+    /// Column of the error in characters, counting from 1.
+    pub column: Option<usize>,
+    /// The offending text, empty when the input ended too early.
+    pub found: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Error without a position yet, see [`ParseError::rebase`].
+    pub fn new(day: u32, expected: impl Into<String>, found: &str) -> Self {
+        Self {
+            day,
+            line: None,
+            column: None,
+            found: found.to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Error about `found`, a slice of `text`, placed where it is in `text`.
+    pub fn within(day: u32, text: &str, found: &str, expected: impl Into<String>) -> Self {
+        Self::new(day, expected, found).rebase(text, found)
+    }
+
+    /// Error about `text` ending before `expected`.
+    pub fn end_of(day: u32, text: &str, expected: impl Into<String>) -> Self {
+        Self::within(day, text, &text[text.len()..], expected)
+    }
+
+    /// Make the position relative to `outer`, given it was relative to `inner`, a slice of
+    /// `outer`. An error without a position is placed at the start of `inner`.
+    ///
+    /// Nothing changes if `inner` is not part of `outer`.
+    pub fn rebase(mut self, outer: &str, inner: &str) -> Self {
+        let Some((line, column)) = position(outer, inner) else {
+            return self;
+        };
+        match self.line {
+            None | Some(1) => {
+                self.column = Some(column + self.column.unwrap_or(1) - 1);
+                self.line = Some(line);
+            }
+            Some(l) => self.line = Some(line + l - 1),
+        }
+        self
+    }
+}
+
+/// Line and column of the start of `inner` in `outer`, `None` if it is not a slice of it.
+fn position(outer: &str, inner: &str) -> Option<(usize, usize)> {
+    let offset = (inner.as_ptr() as usize).checked_sub(outer.as_ptr() as usize)?;
+    if offset + inner.len() > outer.len() {
+        return None;
+    }
+    let before = outer.get(..offset)?;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    Some((line, before[line_start..].chars().count() + 1))
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {}", self.day)?;
+        if let Some(line) = self.line {
+            write!(f, ", line {line}")?;
+        }
+        if let Some(column) = self.column {
+            write!(f, ", column {column}")?;
+        }
+        write!(f, ": expected {}, ", self.expected)?;
+        if self.found.is_empty() {
+            write!(f, "found nothing")
+        } else {
+            write!(f, "found `{}`", self.found)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parse `s` with its [`FromStr`] impl, e.g. a number, reporting `expected` if it fails.
+pub fn token<T: FromStr>(day: u32, s: &str, expected: &str) -> Result<T, ParseError> {
+    s.parse().map_err(|_| ParseError::new(day, expected, s))
+}
+
+/// The parsed input, or a panic with the error as its message.
+///
+/// Solvers return bare answers, so this is how a bad input is reported: the runner turns the
+/// panic into the failure of the part.
+pub fn or_panic<T>(parsed: Result<T, ParseError>) -> T {
+    parsed.unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Parse every non blank line of `input`, trimmed, with `parse`, a plain function taking the
+/// line where [`combinator::lines`](crate::combinator::lines) takes a parser.
+pub fn each_line<'a, T>(
+    input: &'a str,
+    mut parse: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| parse(line).map_err(|e| e.rebase(input, line)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebase() {
+        let input = "1 2\n  3 x 5\n";
+        let line = input.lines().nth(1).unwrap().trim();
+        let x = &line[2..3];
+        let error = token::<u32>(1, x, "a number").unwrap_err();
+        assert_eq!((error.line, error.column), (None, None));
+        let error = error.rebase(line, x).rebase(input, line);
+        assert_eq!((error.line, error.column), (Some(2), Some(5)));
+        assert_eq!(
+            error.to_string(),
+            "day 1, line 2, column 5: expected a number, found `x`"
+        );
+
+        // a line below the first keeps its column
+        let block = &input[2..];
+        let error = ParseError::within(1, block, &block[6..7], "a digit");
+        assert_eq!((error.line, error.column), (Some(2), Some(5)));
+        let error = error.rebase(input, block);
+        assert_eq!((error.line, error.column), (Some(2), Some(5)));
+
+        let error = ParseError::end_of(3, "move 1", "`from`");
+        assert_eq!(
+            error.to_string(),
+            "day 3, line 1, column 7: expected `from`, found nothing"
+        );
+        assert_eq!(ParseError::new(3, "a", "b").rebase("abc", "def").line, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "day 1, line 2, column 1: expected a number, found `y`")]
+    fn test_or_panic() {
+        assert_eq!(or_panic(token::<u8>(1, "7", "a number")), 7);
+        or_panic(each_line("1\ny", |l| token::<u8>(1, l, "a number")));
+    }
+
+    #[test]
+    fn test_each_line() {
+        let parsed = each_line("\n 1\n\n 2 \n", |l| token::<u8>(1, l, "a number"));
+        assert_eq!(parsed, Ok(vec![1, 2]));
+        let error = each_line("1\n\n  y\n", |l| token::<u8>(1, l, "a number")).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), Some(3)));
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::{geometry::Point, grid::Grid};
+
+/// Colour of a pixel.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Self = Self(0, 0, 0);
+    pub const WHITE: Self = Self(255, 255, 255);
+
+    /// Colour `t` of the way from `self` to `other`, `t` going from 0 to 1.
+    pub fn blend(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+        Self(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+}
+
+/// A state that can be drawn as a picture, one pixel per cell.
+pub trait Render {
+    /// Number of cells across and down.
+    fn size(&self) -> (usize, usize);
+
+    /// Colour of the cell at `pos`, counted from the top left corner of the picture.
+    fn colour(&self, pos: Point<usize>) -> Rgb;
+
+    /// Where the top left corner of the picture lies in the world, so that pictures of a state
+    /// whose bounds grow still line up.
+    fn origin(&self) -> Point<isize> {
+        Point::new(0, 0)
+    }
+
+    /// Colour of an empty cell.
+    fn background(&self) -> Rgb {
+        Rgb::BLACK
+    }
+
+    fn render(&self) -> Grid<Rgb> {
+        let (width, height) = self.size();
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Point::new(x, y)))
+            .map(|pos| self.colour(pos))
+            .collect();
+        Grid::from_cells(width, cells)
+    }
+
+    /// Write the picture to `path` with cells of `factor` by `factor` pixels, see [`save`].
+    fn save(&self, path: &Path, factor: usize) -> io::Result<()> {
+        save(&scale(&self.render(), factor), path)
+    }
+}
+
+/// A picture already drawn.
+impl Render for Grid<Rgb> {
+    fn size(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    fn colour(&self, pos: Point<usize>) -> Rgb {
+        self[pos]
+    }
+}
+
+/// Every pixel of `image` blown up to a square of `factor` by `factor` pixels.
+pub fn scale(image: &Grid<Rgb>, factor: usize) -> Grid<Rgb> {
+    let cells = (0..image.height() * factor)
+        .flat_map(|y| (0..image.width() * factor).map(move |x| Point::new(x, y)))
+        .map(|p| image[Point::new(p.x / factor, p.y / factor)])
+        .collect();
+    Grid::from_cells(image.width() * factor, cells)
+}
+
+/// Write `image` as a PPM or a PNG file, picked by the extension of `path`.
+///
+/// An empty image, such as one scaled by 0, is refused rather than written.
+pub fn save(image: &Grid<Rgb>, path: &Path) -> io::Result<()> {
+    if image.width() == 0 || image.height() == 0 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} would be an empty picture", path.display()),
+        ));
+    }
+    let bytes = match path.extension().and_then(|e| e.to_str()) {
+        Some("ppm") => ppm(image),
+        Some("png") => png(image),
+        _ => {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is neither a .ppm nor a .png file", path.display()),
+            ));
+        }
+    };
+    fs::write(path, bytes)
+}
+
+/// Binary PPM (`P6`) encoding of `image`.
+pub fn ppm(image: &Grid<Rgb>) -> Vec<u8> {
+    let mut bytes = format!("P6\n{} {}\n255\n", image.width(), image.height()).into_bytes();
+    for (_, &Rgb(r, g, b)) in image.iter() {
+        bytes.extend([r, g, b]);
+    }
+    bytes
+}
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// PNG encoding of `image`, 8 bit RGB without compression.
+///
+/// Panics on an empty image, a PNG has at least one pixel.
+pub fn png(image: &Grid<Rgb>) -> Vec<u8> {
+    assert!(
+        image.width() > 0 && image.height() > 0,
+        "a PNG needs at least one pixel"
+    );
+    let mut header = vec![];
+    header.extend((image.width() as u32).to_be_bytes());
+    header.extend((image.height() as u32).to_be_bytes());
+    // bit depth, colour type RGB, then default compression, filter and no interlace
+    header.extend([8, 2, 0, 0, 0]);
+
+    // every scanline starts with its filter type, none here
+    let mut scanlines = Vec::with_capacity(image.height() * (1 + 3 * image.width()));
+    for row in image.rows() {
+        scanlines.push(0);
+        for &Rgb(r, g, b) in row {
+            scanlines.extend([r, g, b]);
+        }
+    }
+
+    let mut bytes = PNG_SIGNATURE.to_vec();
+    push_chunk(&mut bytes, b"IHDR", &header);
+    push_chunk(&mut bytes, b"IDAT", &zlib_stored(&scanlines));
+    push_chunk(&mut bytes, b"IEND", &[]);
+    bytes
+}
+
+/// Length, type, data and CRC of the type and data.
+fn push_chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend((data.len() as u32).to_be_bytes());
+    bytes.extend(kind);
+    bytes.extend(data);
+    bytes.extend(crc32(kind.iter().chain(data)).to_be_bytes());
+}
+
+/// Zlib stream of `data` in stored, uncompressed, deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // deflate with a 32K window, no preset dictionary, and the check bits of this header
+    let mut bytes = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        bytes.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        bytes.push(u8::from(last));
+        bytes.extend(len.to_le_bytes());
+        bytes.extend((!len).to_le_bytes());
+        bytes.extend(block);
+    }
+    bytes.extend(adler32(data).to_be_bytes());
+    bytes
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+/// CRC-32 as used by PNG and zip.
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let crc = bytes.into_iter().fold(u32::MAX, |crc, &b| {
+        CRC_TABLE[((crc ^ u32::from(b)) & 0xff) as usize] ^ (crc >> 8)
+    });
+    !crc
+}
+
+/// Adler-32 checksum ending a zlib stream.
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1, 0);
+    // sums of this many bytes cannot overflow before the modulo
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> Grid<Rgb> {
+        Grid::from_cells(2, vec![Rgb::BLACK, Rgb(1, 2, 3), Rgb::WHITE, Rgb(9, 8, 7)])
+    }
+
+    /// Chunks of a PNG, checking their CRC on the way.
+    fn chunks(mut bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut chunks = vec![];
+        while !bytes.is_empty() {
+            let len = u32::from_be_bytes(bytes[..4].try_into().unwrap()) as usize;
+            let (kind, rest) = bytes[4..].split_at(4);
+            let (data, rest) = rest.split_at(len);
+            let crc = u32::from_be_bytes(rest[..4].try_into().unwrap());
+            assert_eq!(crc, crc32(kind.iter().chain(data)));
+            chunks.push((String::from_utf8(kind.to_vec()).unwrap(), data.to_vec()));
+            bytes = &rest[4..];
+        }
+        chunks
+    }
+
+    /// Data of a zlib stream made of stored blocks.
+    fn inflate_stored(zlib: &[u8]) -> Vec<u8> {
+        assert_eq!(u16::from_be_bytes([zlib[0], zlib[1]]) % 31, 0);
+        let mut data = vec![];
+        let mut at = 2;
+        loop {
+            let last = zlib[at] & 1 == 1;
+            assert_eq!(zlib[at] >> 1, 0, "not a stored block");
+            let len = u16::from_le_bytes([zlib[at + 1], zlib[at + 2]]);
+            let nlen = u16::from_le_bytes([zlib[at + 3], zlib[at + 4]]);
+            assert_eq!(len, !nlen);
+            at += 5;
+            data.extend(&zlib[at..at + len as usize]);
+            at += len as usize;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(zlib[at..], adler32(&data).to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(&[]), 1);
+        // large enough to need the modulo between chunks
+        let bytes: Vec<u8> = (0..20_000).map(|i| (i % 256) as u8).collect();
+        let naive = bytes.iter().fold((1u64, 0u64), |(a, b), &x| {
+            let a = (a + u64::from(x)) % 65521;
+            (a, (b + a) % 65521)
+        });
+        assert_eq!(adler32(&bytes), ((naive.1 << 16) | naive.0) as u32);
+    }
+
+    #[test]
+    fn test_ppm() {
+        let bytes = ppm(&image());
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&bytes[..header.len()], header);
+        assert_eq!(
+            bytes[header.len()..],
+            [0, 0, 0, 1, 2, 3, 255, 255, 255, 9, 8, 7]
+        );
+    }
+
+    #[test]
+    fn test_png() {
+        let bytes = png(&image());
+        assert_eq!(bytes[..8], PNG_SIGNATURE);
+        let parts = chunks(&bytes[8..]);
+        let kinds: Vec<&str> = parts.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(kinds, ["IHDR", "IDAT", "IEND"]);
+        assert_eq!(parts[0].1, [0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        assert_eq!(
+            inflate_stored(&parts[1].1),
+            [0, 0, 0, 0, 1, 2, 3, 0, 255, 255, 255, 9, 8, 7]
+        );
+
+        // a picture spanning several stored blocks
+        let big = Grid::new(200, 200, Rgb(1, 2, 3));
+        let parts = chunks(&png(&big)[8..]);
+        let data = inflate_stored(&parts[1].1);
+        assert_eq!(data.len(), 200 * (1 + 3 * 200));
+        assert_eq!(data[..4], [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_render() {
+        struct Checkers;
+        impl Render for Checkers {
+            fn size(&self) -> (usize, usize) {
+                (3, 2)
+            }
+
+            fn colour(&self, pos: Point<usize>) -> Rgb {
+                if (pos.x + pos.y).is_multiple_of(2) {
+                    Rgb::WHITE
+                } else {
+                    Rgb::BLACK
+                }
+            }
+        }
+        let image = Checkers.render();
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(image.row(1), [Rgb::BLACK, Rgb::WHITE, Rgb::BLACK]);
+        let scaled = scale(&image, 2);
+        assert_eq!((scaled.width(), scaled.height()), (6, 4));
+        assert_eq!(scaled[Point::new(3, 1)], Rgb::BLACK);
+        assert_eq!(scaled[Point::new(3, 2)], Rgb::WHITE);
+
+        assert_eq!(Rgb::BLACK.blend(Rgb(100, 200, 50), 0.5), Rgb(50, 100, 25));
+        assert_eq!(Rgb::BLACK.blend(Rgb::WHITE, 2.0), Rgb::WHITE);
+        let error = save(&image, Path::new("picture.gif")).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        // refused before anything is written, so the path is never created
+        let error = Checkers.save(Path::new("empty.png"), 0).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert!(!Path::new("empty.png").exists());
+    }
+}
@@ -0,0 +1,433 @@
+use std::{
+    any::Any,
+    fmt::Write,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Answer, Part, Puzzle, SOLVERS,
+    alloc::{self, AllocStats, format_bytes},
+    input::{self, Source},
+};
+
+/// Result of running a single part of a day.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub day: u32,
+    pub part: Part,
+    /// The answer, or the reason there is none.
+    pub answer: Result<Answer, String>,
+    pub elapsed: Duration,
+    /// CPU time the part used, on platforms with a clock per thread.
+    pub cpu: Option<Duration>,
+    /// Memory used, when built with the counting allocator.
+    pub alloc: Option<AllocStats>,
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+/// CPU time used by the calling thread so far.
+#[cfg(unix)]
+fn thread_cpu_time() -> Option<Duration> {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `now` is a valid timespec for the clock to write to
+    let read = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut now) };
+    (read == 0).then(|| Duration::new(now.tv_sec as u64, now.tv_nsec as u32))
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// Run one part, turning a panic (or a `todo!()`) into an error instead of unwinding further.
+///
+/// The part runs on the calling thread, so that its CPU time is the one of the thread.
+pub fn run_part(puzzle: &dyn Puzzle, part: Part, input: &str) -> Outcome {
+    let start = Instant::now();
+    let cpu_start = thread_cpu_time();
+    let (answer, alloc) = alloc::measure(|| {
+        panic::catch_unwind(AssertUnwindSafe(|| puzzle.run(part, input)))
+            .map_err(|payload| panic_message(payload.as_ref()))
+    });
+    Outcome {
+        day: puzzle.day(),
+        part,
+        answer,
+        elapsed: start.elapsed(),
+        cpu: thread_cpu_time()
+            .zip(cpu_start)
+            .map(|(end, start)| end - start),
+        alloc,
+    }
+}
+
+/// Run `f` with the default panic message silenced, panics are reported through [`Outcome`]s
+/// instead.
+pub fn quietly<T>(f: impl FnOnce() -> T) -> T {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = f();
+    panic::set_hook(hook);
+    result
+}
+
+fn load_failed(day: u32, part: Part, e: &anyhow::Error) -> Outcome {
+    Outcome {
+        day,
+        part,
+        answer: Err(format!("{e:#}")),
+        elapsed: Duration::ZERO,
+        cpu: Some(Duration::ZERO),
+        alloc: None,
+    }
+}
+
+/// Run the given parts of one day, a missing input is reported as a failure of every part.
+pub fn run_day(puzzle: &dyn Puzzle, source: &Source, parts: &[Part]) -> Vec<Outcome> {
+    let day = puzzle.day();
+    match input::load(day, source) {
+        Ok(input) => parts.iter().map(|&p| run_part(puzzle, p, &input)).collect(),
+        Err(e) => parts.iter().map(|&p| load_failed(day, p, &e)).collect(),
+    }
+}
+
+/// Stack size of worker threads, solutions recurse about as deep as they can on the main thread.
+const WORKER_STACK_SIZE: usize = 16 << 20;
+
+/// Number of threads to use for `jobs`, where 0 means one per core.
+pub fn worker_count(jobs: usize) -> usize {
+    if jobs == 0 {
+        thread::available_parallelism().map_or(1, NonZeroUsize::get)
+    } else {
+        jobs
+    }
+}
+
+/// Run the given parts of every registered day on `jobs` threads, reading each input from its
+/// default location.
+pub fn run_all(parts: &[Part], jobs: usize) -> Vec<Outcome> {
+    let inputs: Vec<_> = SOLVERS
+        .iter()
+        .map(|p| input::load(p.day(), &Source::Default))
+        .collect();
+    run_parallel(SOLVERS, &inputs, parts, jobs)
+}
+
+/// Run the given parts of each puzzle on its input, on `jobs` threads.
+///
+/// Every part is a separate task, outcomes are returned in the order of `puzzles` and `parts`
+/// whichever finishes first.
+fn run_parallel(
+    puzzles: &[&dyn Puzzle],
+    inputs: &[anyhow::Result<String>],
+    parts: &[Part],
+    jobs: usize,
+) -> Vec<Outcome> {
+    let tasks: Vec<(usize, Part)> = (0..puzzles.len())
+        .flat_map(|i| parts.iter().map(move |&p| (i, p)))
+        .collect();
+    let results: Vec<Mutex<Option<Outcome>>> = tasks.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+    let work = || {
+        loop {
+            let idx = next.fetch_add(1, Ordering::Relaxed);
+            let Some(&(i, part)) = tasks.get(idx) else {
+                break;
+            };
+            let outcome = match &inputs[i] {
+                Ok(input) => run_part(puzzles[i], part, input),
+                Err(e) => load_failed(puzzles[i].day(), part, e),
+            };
+            *results[idx].lock().unwrap() = Some(outcome);
+        }
+    };
+    quietly(|| {
+        thread::scope(|s| {
+            for _ in 0..worker_count(jobs).min(tasks.len()) {
+                thread::Builder::new()
+                    .stack_size(WORKER_STACK_SIZE)
+                    .spawn_scoped(s, work)
+                    .expect("failed to spawn worker thread");
+            }
+        })
+    });
+    results
+        .into_iter()
+        .map(|r| r.into_inner().unwrap().expect("every task is run"))
+        .collect()
+}
+
+impl Outcome {
+    /// The outcome as a single line JSON object.
+    ///
+    /// Integer answers are written as numbers, text and grid answers as strings. `error` is
+    /// `null` unless the part failed, in which case `answer` and `type` are `null`. With the
+    /// counting allocator `peak_bytes`, `allocated_bytes` and `allocations` are added.
+    pub fn to_json(&self) -> String {
+        let (answer, kind, error) = match &self.answer {
+            Ok(Answer::Integer(n)) => (n.to_string(), json_string("integer"), "null".to_string()),
+            Ok(a) => (
+                json_string(&a.to_string()),
+                json_string(a.kind()),
+                "null".to_string(),
+            ),
+            Err(e) => ("null".to_string(), "null".to_string(), json_string(e)),
+        };
+        let alloc = self
+            .alloc
+            .map(|a| {
+                format!(
+                    r#","peak_bytes":{},"allocated_bytes":{},"allocations":{}"#,
+                    a.peak, a.total, a.count
+                )
+            })
+            .unwrap_or_default();
+        format!(
+            r#"{{"day":{},"part":{},"answer":{answer},"type":{kind},"duration_ns":{}{alloc},"error":{error}}}"#,
+            self.day,
+            self.part.number(),
+            self.elapsed.as_nanos()
+        )
+    }
+}
+
+/// Quote and escape `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Print one JSON object per outcome, one per line.
+pub fn print_json(outcomes: &[Outcome]) {
+    for o in outcomes {
+        println!("{}", o.to_json());
+    }
+}
+
+/// Human friendly duration with a fixed unit per magnitude.
+pub fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+    if nanos < 1_000 {
+        format!("{nanos}ns")
+    } else if nanos < 1_000_000 {
+        format!("{:.2}µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", nanos as f64 / 1e9)
+    }
+}
+
+/// Print outcomes as a table, multi-line answers are printed below their row.
+///
+/// Memory columns are added when built with the counting allocator. The summary line has the
+/// CPU time of all parts summed up, when the platform measures it, and the wall time it took if
+/// they ran concurrently.
+pub fn print_table(outcomes: &[Outcome], wall: Option<Duration>) {
+    let answer_width = outcomes
+        .iter()
+        .filter_map(|o| o.answer.as_ref().ok())
+        .map(|a| a.to_string())
+        .filter(|a| !a.contains('\n'))
+        .map(|a| a.len())
+        .max()
+        .unwrap_or(0)
+        .max("answer".len());
+    let with_alloc = outcomes.iter().any(|o| o.alloc.is_some());
+    let alloc_columns = |peak: &str, total: &str, count: &str| {
+        if with_alloc {
+            format!("  {peak:>10}  {total:>10}  {count:>10}")
+        } else {
+            String::new()
+        }
+    };
+    println!(
+        "{:>3}  {:>4}  {:<answer_width$}  {:>10}{}",
+        "day",
+        "part",
+        "answer",
+        "time",
+        alloc_columns("peak", "allocated", "allocs")
+    );
+    let width = 3 + 2 + 4 + 2 + answer_width + 2 + 10 + if with_alloc { 36 } else { 0 };
+    println!("{}", "-".repeat(width));
+    for o in outcomes {
+        let (answer, extra) = match &o.answer {
+            Ok(a) if matches!(a, Answer::Grid(_)) => {
+                ("(see below)".to_string(), Some(a.to_string()))
+            }
+            Ok(a) => (a.to_string(), None),
+            Err(e) => (format!("FAILED: {e}"), None),
+        };
+        let alloc = match o.alloc {
+            Some(a) => alloc_columns(
+                &format_bytes(a.peak),
+                &format_bytes(a.total),
+                &a.count.to_string(),
+            ),
+            None => alloc_columns("", "", ""),
+        };
+        println!(
+            "{:>3}  {:>4}  {:<answer_width$}  {:>10}{alloc}",
+            o.day,
+            o.part.number(),
+            answer,
+            format_duration(o.elapsed)
+        );
+        if let Some(extra) = extra {
+            for line in extra.lines() {
+                println!("{:>11}{line}", "");
+            }
+        }
+    }
+    let failed = outcomes.iter().filter(|o| o.answer.is_err()).count();
+    let cpu = outcomes
+        .iter()
+        .map(|o| o.cpu)
+        .sum::<Option<Duration>>()
+        .map(|c| format!(", cpu {}", format_duration(c)))
+        .unwrap_or_default();
+    let wall = wall
+        .map(|w| format!(", wall {}", format_duration(w)))
+        .unwrap_or_default();
+    println!("{} parts, {failed} failed{cpu}{wall}", outcomes.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Solver, solver};
+
+    #[test]
+    fn test_run_part_catches_panic() {
+        let outcome = run_part(solver(16).unwrap(), Part::Two, "");
+        assert_eq!(outcome.answer, Err("not yet implemented".to_string()));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let outcome = |answer| Outcome {
+            day: 10,
+            part: Part::Two,
+            answer,
+            elapsed: Duration::from_nanos(1500),
+            cpu: None,
+            alloc: None,
+        };
+        assert_eq!(
+            outcome(Ok(Answer::Integer(-3))).to_json(),
+            r#"{"day":10,"part":2,"answer":-3,"type":"integer","duration_ns":1500,"error":null}"#
+        );
+        assert_eq!(
+            outcome(Ok(Answer::Grid("#.\n.#\n".to_string()))).to_json(),
+            r##"{"day":10,"part":2,"answer":"#.\n.#","type":"grid","duration_ns":1500,"error":null}"##
+        );
+        assert_eq!(
+            outcome(Err("bad \"input\"".to_string())).to_json(),
+            r#"{"day":10,"part":2,"answer":null,"type":null,"duration_ns":1500,"error":"bad \"input\""}"#
+        );
+        let mut measured = outcome(Ok(Answer::Integer(1)));
+        measured.alloc = Some(AllocStats {
+            peak: 10,
+            total: 30,
+            count: 3,
+        });
+        assert_eq!(
+            measured.to_json(),
+            r#"{"day":10,"part":2,"answer":1,"type":"integer","duration_ns":1500,"peak_bytes":10,"allocated_bytes":30,"allocations":3,"error":null}"#
+        );
+    }
+
+    /// Sleeps for as many milliseconds as its input says, so early tasks finish last.
+    struct Sleepy(u32);
+
+    impl Solver for Sleepy {
+        const DAY: u32 = 1;
+
+        fn part1(&self, input: &str) -> Answer {
+            std::thread::sleep(Duration::from_millis(input.parse().unwrap()));
+            Answer::Integer(self.0 as i64)
+        }
+
+        fn part2(&self, _input: &str) -> Answer {
+            todo!()
+        }
+    }
+
+    #[test]
+    fn test_run_parallel_order() {
+        let puzzles: &[&dyn Puzzle] = &[&Sleepy(1), &Sleepy(2), &Sleepy(3)];
+        let inputs = vec![
+            Ok("30".to_string()),
+            Err(anyhow::anyhow!("missing")),
+            Ok("0".to_string()),
+        ];
+        let outcomes = run_parallel(puzzles, &inputs, &Part::ALL, 4);
+        let answers: Vec<_> = outcomes
+            .iter()
+            .map(|o| (o.part, o.answer.clone()))
+            .collect();
+        let todo = || Err("not yet implemented".to_string());
+        assert_eq!(
+            answers,
+            [
+                (Part::One, Ok(Answer::Integer(1))),
+                (Part::Two, todo()),
+                (Part::One, Err("missing".to_string())),
+                (Part::Two, Err("missing".to_string())),
+                (Part::One, Ok(Answer::Integer(3))),
+                (Part::Two, todo()),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cpu_time() {
+        // sleeping takes time but no CPU
+        let outcome = run_part(&Sleepy(1), Part::One, "50");
+        assert!(outcome.elapsed >= Duration::from_millis(50));
+        assert!(outcome.cpu.unwrap() < Duration::from_millis(25));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_nanos(12)), "12ns");
+        assert_eq!(format_duration(Duration::from_micros(1500)), "1.50ms");
+        assert_eq!(format_duration(Duration::from_secs(2)), "2.00s");
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::input;
+
+/// Name of the example input created next to the real one.
+pub const EXAMPLE_FILE: &str = "example";
+
+/// First line of a generated module, which `new` may replace for as long as it is there.
+const PLACEHOLDER: &str = "// placeholder from `new`, delete this line before solving the day";
+
+const TEMPLATE: &str = r#"// placeholder from `new`, delete this line before solving the day
+use crate::{Answer, Solver, bench::parsing};
+
+pub struct DayNN;
+
+impl Solver for DayNN {
+    const DAY: u32 = N;
+
+    fn part1(&self, input: &str) -> Answer {
+        first(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        second(input).into()
+    }
+}
+
+fn parse(input: &str) -> Vec<&str> {
+    parsing(|| input.lines().collect())
+}
+
+fn first(input: &str) -> usize {
+    let _lines = parse(input);
+    todo!()
+}
+
+fn second(input: &str) -> usize {
+    let _lines = parse(input);
+    todo!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the example from the puzzle text, also in input/dayNN/example
+    static INPUT: &str = r"
+";
+
+    #[test]
+    #[ignore = "example answer not filled in yet"]
+    fn test_first() {
+        assert_eq!(first(INPUT), 0);
+    }
+
+    #[test]
+    #[ignore = "example answer not filled in yet"]
+    fn test_second() {
+        assert_eq!(second(INPUT), 0);
+    }
+}
+"#;
+
+/// Source of a new, unsolved day.
+fn module_source(day: u32) -> String {
+    TEMPLATE
+        .replace("NN", &format!("{day:02}"))
+        .replace("= N;", &format!("= {day};"))
+}
+
+/// A module counts as implemented once it has a solver, unless it still starts with the
+/// [`PLACEHOLDER`] line of a generated one.
+fn is_implemented(source: &str) -> bool {
+    source.contains("impl Solver") && !source.starts_with(PLACEHOLDER)
+}
+
+/// Add the `mod` declaration and [`SOLVERS`](crate::SOLVERS) entry of `day` to the source of
+/// `lib.rs`, keeping both in calendar order.
+fn register(lib: &str, day: u32) -> Result<String> {
+    let module = format!("day{day:02}");
+    let mod_line = format!("pub mod {module};");
+    let entry = format!("    &{module}::Day{day:02},");
+    let mut lines: Vec<String> = lib.lines().map(str::to_string).collect();
+
+    if !lines.contains(&mod_line) {
+        let at = lines
+            .iter()
+            .position(|l| l.starts_with("pub mod day") && *l > mod_line)
+            .or_else(|| {
+                lines
+                    .iter()
+                    .rposition(|l| l.starts_with("pub mod day"))
+                    .map(|i| i + 1)
+            })
+            .context("no day modules declared in lib.rs")?;
+        lines.insert(at, mod_line);
+    }
+
+    let start = lines
+        .iter()
+        .position(|l| l.starts_with("pub static SOLVERS"))
+        .context("no SOLVERS registry in lib.rs")?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .position(|l| l == "];")
+            .context("unterminated SOLVERS registry in lib.rs")?;
+    if !lines[start + 1..end].contains(&entry) {
+        let at = lines[start + 1..end]
+            .iter()
+            .position(|l| *l > entry)
+            .map_or(end, |i| start + 1 + i);
+        lines.insert(at, entry);
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    Ok(out)
+}
+
+/// Generate the module, input folder and registry entry of `day` in the crate at `root`.
+///
+/// Placeholder modules are replaced, an implemented one is left alone and reported as an error.
+/// Files that already exist in the input folder are kept. Returns the paths written.
+pub fn scaffold(root: &Path, day: u32) -> Result<Vec<PathBuf>> {
+    if !(1..=25).contains(&day) {
+        bail!("day {day} is not a puzzle day, expected 1 to 25");
+    }
+    let module_path = root.join("src").join(format!("day{day:02}.rs"));
+    match fs::read_to_string(&module_path) {
+        Ok(s) if is_implemented(&s) => bail!(
+            "day {day} is already implemented in {}",
+            module_path.display()
+        ),
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read {}", module_path.display()));
+        }
+    }
+    let mut written = vec![];
+
+    fs::write(&module_path, module_source(day))
+        .with_context(|| format!("failed to write {}", module_path.display()))?;
+    written.push(module_path);
+
+    let dir = root.join(input::day_dir(day));
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let example = dir.join(EXAMPLE_FILE);
+    if !example.exists() {
+        fs::write(&example, "")
+            .with_context(|| format!("failed to write {}", example.display()))?;
+        written.push(example);
+    }
+
+    let lib_path = root.join("src").join("lib.rs");
+    let lib = fs::read_to_string(&lib_path)
+        .with_context(|| format!("failed to read {}", lib_path.display()))?;
+    let registered = register(&lib, day)?;
+    if registered != lib {
+        fs::write(&lib_path, registered)
+            .with_context(|| format!("failed to write {}", lib_path.display()))?;
+        written.push(lib_path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static LIB: &str = "pub mod bench;
+pub mod day01;
+pub mod day03;
+pub mod input;
+
+pub static SOLVERS: &[&dyn Puzzle] = &[
+    &day01::Day01,
+    &day03::Day03,
+];
+";
+
+    #[test]
+    fn test_register() {
+        let lib = register(LIB, 2).unwrap();
+        assert_eq!(
+            lib,
+            "pub mod bench;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod input;
+
+pub static SOLVERS: &[&dyn Puzzle] = &[
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+];
+"
+        );
+        assert_eq!(register(&lib, 2).unwrap(), lib);
+        assert!(
+            register(LIB, 4)
+                .unwrap()
+                .contains("pub mod day03;\npub mod day04;\n")
+        );
+        assert!(
+            register(LIB, 4)
+                .unwrap()
+                .ends_with("    &day04::Day04,\n];\n")
+        );
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let root = std::env::temp_dir().join(format!("aoc_scaffold_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), LIB).unwrap();
+        fs::write(
+            root.join("src/day02.rs"),
+            "pub fn run() {\n    todo!()\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/day03.rs"), "impl Solver for Day03 {}\n").unwrap();
+
+        let written = scaffold(&root, 2).unwrap();
+        assert_eq!(written.len(), 3);
+        let module = fs::read_to_string(root.join("src/day02.rs")).unwrap();
+        assert!(module.contains("pub struct Day02;"));
+        assert!(module.contains("const DAY: u32 = 2;"));
+        assert!(module.contains("static INPUT: &str = r\"\n\";"));
+        assert!(!module.contains("include_str!"));
+        assert!(root.join("input/day02/example").exists());
+        let lib = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("&day02::Day02,"));
+
+        // an untouched placeholder is generated again, keeping the example
+        fs::write(root.join("input/day02/example"), "1 2 3\n").unwrap();
+        assert_eq!(scaffold(&root, 2).unwrap().len(), 1);
+        let example = fs::read_to_string(root.join("input/day02/example")).unwrap();
+        assert_eq!(example, "1 2 3\n");
+        // but not once the day is being solved
+        fs::write(root.join("src/day02.rs"), &module[PLACEHOLDER.len() + 1..]).unwrap();
+        assert!(scaffold(&root, 2).is_err());
+        assert!(scaffold(&root, 3).is_err());
+        assert!(scaffold(&root, 26).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
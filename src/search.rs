@@ -0,0 +1,333 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// Cost of an edge of a weighted search, `Default` is zero.
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<C: Copy + Ord + Add<Output = C> + Default> Cost for C {}
+
+/// Item of a [`BinaryHeap`] popped by lowest cost first, used by Dijkstra's algorithm and A*.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+pub struct ScoredItem<N, T> {
+    pub cost: N,
+    pub item: T,
+}
+
+impl<N: Ord, T: Eq> Ord for ScoredItem<N, T> {
+    fn cmp(&self, other: &ScoredItem<N, T>) -> Ordering {
+        other.cost.cmp(&self.cost)
+    }
+}
+
+impl<N: Ord, T: Eq> PartialOrd for ScoredItem<N, T> {
+    fn partial_cmp(&self, other: &ScoredItem<N, T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Nodes reached by a search, with their cost and the node they were reached from.
+#[derive(Debug, Clone)]
+pub struct Reached<N, C> {
+    nodes: HashMap<N, (C, Option<N>)>,
+}
+
+impl<N: Eq + Hash + Clone, C: Copy> Reached<N, C> {
+    fn new() -> Self {
+        Self {
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    /// Cost of the cheapest path found to `node`.
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.nodes.get(node).map(|(cost, _)| *cost)
+    }
+
+    /// Nodes from one of the starts to `node`, both included.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        let mut path = vec![node.clone()];
+        let mut current = self.nodes.get(node)?;
+        while let Some(prev) = &current.1 {
+            path.push(prev.clone());
+            current = &self.nodes[prev];
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every node reached, with its cost, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&N, C)> + '_ {
+        self.nodes.iter().map(|(node, (cost, _))| (node, *cost))
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn path(&self, goal: Option<N>) -> Option<Path<N, C>> {
+        let goal = goal?;
+        Some(Path {
+            cost: self.cost(&goal)?,
+            nodes: self.path_to(&goal)?,
+        })
+    }
+}
+
+/// A path found by a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    /// From the start to the goal, both included.
+    pub nodes: Vec<N>,
+    pub cost: C,
+}
+
+impl<N, C> Path<N, C> {
+    /// Number of edges followed.
+    pub fn steps(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    pub fn start(&self) -> &N {
+        &self.nodes[0]
+    }
+
+    pub fn goal(&self) -> &N {
+        &self.nodes[self.nodes.len() - 1]
+    }
+}
+
+fn breadth_first<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> (Reached<N, usize>, Option<N>)
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut reached = Reached::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !reached.contains(&start) {
+            reached.nodes.insert(start.clone(), (0, None));
+            queue.push_back((start, 0));
+        }
+    }
+    while let Some((node, steps)) = queue.pop_front() {
+        if goal(&node) {
+            return (reached, Some(node));
+        }
+        for next in neighbours(&node) {
+            if !reached.contains(&next) {
+                reached
+                    .nodes
+                    .insert(next.clone(), (steps + 1, Some(node.clone())));
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    (reached, None)
+}
+
+fn best_first<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut goal: impl FnMut(&N) -> bool,
+) -> (Reached<N, C>, Option<N>)
+where
+    N: Eq + Hash + Clone,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut reached = Reached::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if !reached.contains(&start) {
+            reached.nodes.insert(start.clone(), (C::default(), None));
+            queue.push(ScoredItem {
+                cost: heuristic(&start),
+                item: (start, C::default()),
+            });
+        }
+    }
+    while let Some(ScoredItem {
+        item: (node, cost), ..
+    }) = queue.pop()
+    {
+        // a cheaper path to the node was queued after this one
+        if reached.cost(&node).is_some_and(|best| best < cost) {
+            continue;
+        }
+        if goal(&node) {
+            return (reached, Some(node));
+        }
+        for (next, weight) in neighbours(&node) {
+            let next_cost = cost + weight;
+            if reached.cost(&next).is_none_or(|best| next_cost < best) {
+                reached
+                    .nodes
+                    .insert(next.clone(), (next_cost, Some(node.clone())));
+                queue.push(ScoredItem {
+                    cost: next_cost + heuristic(&next),
+                    item: (next, next_cost),
+                });
+            }
+        }
+    }
+    (reached, None)
+}
+
+/// Breadth first search from every node of `starts` until a node satisfying `goal`, returning
+/// the path with the fewest steps.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, usize>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let (reached, found) = breadth_first(starts, neighbours, goal);
+    reached.path(found)
+}
+
+/// Every node reachable from `starts`, with the fewest steps to get there.
+pub fn bfs_reach<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+) -> Reached<N, usize>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    breadth_first(starts, neighbours, |_| false).0
+}
+
+/// Dijkstra's algorithm from every node of `starts` until a node satisfying `goal`.
+/// `neighbours` yields each next node with the cost of the edge to it.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Eq + Hash + Clone,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let (reached, found) = best_first(starts, neighbours, |_| C::default(), goal);
+    reached.path(found)
+}
+
+/// Every node reachable from `starts`, with the cost of the cheapest path there.
+pub fn dijkstra_reach<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+) -> Reached<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    best_first(starts, neighbours, |_| C::default(), |_| false).0
+}
+
+/// A* from every node of `starts` until a node satisfying `goal`. The path is the cheapest
+/// as long as `heuristic` never overestimates the cost left to a goal.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> C,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Eq + Hash + Clone,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let (reached, found) = best_first(starts, neighbours, heuristic, goal);
+    reached.path(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a -> b -> d costs 2, c -> d costs 5 directly but 2 through e
+    fn graph(node: &char) -> Vec<(char, u32)> {
+        match node {
+            'a' => vec![('b', 1), ('c', 1)],
+            'b' => vec![('d', 1)],
+            'c' => vec![('d', 5), ('e', 1)],
+            'e' => vec![('d', 1)],
+            _ => vec![],
+        }
+    }
+
+    fn unweighted(node: &char) -> Vec<char> {
+        graph(node).into_iter().map(|(n, _)| n).collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let path = bfs(['a'], unweighted, |n| *n == 'd').unwrap();
+        assert_eq!(path.nodes, ['a', 'b', 'd']);
+        assert_eq!((path.steps(), path.cost), (2, 2));
+        assert_eq!(bfs(['a'], unweighted, |n| *n == 'z'), None);
+        // the start closest to the goal wins
+        let path = bfs(['a', 'e'], unweighted, |n| *n == 'd').unwrap();
+        assert_eq!(path.nodes, ['e', 'd']);
+
+        let reached = bfs_reach(['c'], unweighted);
+        assert_eq!(reached.len(), 3);
+        assert_eq!(reached.cost(&'d'), Some(1));
+        assert_eq!(reached.path_to(&'e'), Some(vec!['c', 'e']));
+        assert!(!reached.contains(&'a'));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let path = dijkstra(['a'], graph, |n| *n == 'd').unwrap();
+        assert_eq!((path.nodes, path.cost), (vec!['a', 'b', 'd'], 2));
+        let path = dijkstra(['c'], graph, |n| *n == 'd').unwrap();
+        assert_eq!((path.nodes, path.cost), (vec!['c', 'e', 'd'], 2));
+        let path = dijkstra(['a'], graph, |n| *n == 'a').unwrap();
+        assert_eq!((path.steps(), path.cost), (0, 0));
+
+        let reached = dijkstra_reach(['c'], graph);
+        assert_eq!(reached.cost(&'d'), Some(2));
+        assert_eq!(reached.iter().map(|(_, c)| c).sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn test_astar() {
+        // open 10x10 field, the heuristic leads straight to the corner
+        let neighbours = |&(x, y): &(i32, i32)| {
+            [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                .into_iter()
+                .filter(|(x, y)| (0..10).contains(x) && (0..10).contains(y))
+                .map(|n| (n, 1))
+        };
+        let goal = (9, 9);
+        let heuristic = |&(x, y): &(i32, i32)| (goal.0 - x).abs() + (goal.1 - y).abs();
+        let path = astar([(0, 0)], neighbours, heuristic, |n| *n == goal).unwrap();
+        assert_eq!(path.cost, 18);
+        assert_eq!((path.start(), path.goal()), (&(0, 0), &goal));
+        let plain = dijkstra([(0, 0)], neighbours, |n| *n == goal).unwrap();
+        assert_eq!(plain.cost, path.cost);
+    }
+}
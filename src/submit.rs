@@ -0,0 +1,372 @@
+use std::{
+    fmt, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    Answer, Part,
+    client::Client,
+    input,
+    verify::{ANSWERS_FILE, Expected},
+};
+
+/// Name of the log of submitted answers, kept next to the input.
+pub const SUBMISSIONS_FILE: &str = "submissions";
+
+/// Reply of the puzzle site to a submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Incorrect,
+    TooHigh,
+    TooLow,
+    /// Another answer was submitted too recently, holds how long to wait.
+    RateLimited(String),
+    /// The part is already solved, or part 2 is not unlocked yet.
+    WrongLevel,
+    /// A reply that was not understood, holds the text of it.
+    Unknown(String),
+}
+
+impl Verdict {
+    /// Read the verdict from the page returned for a submission.
+    pub fn parse(page: &str) -> Self {
+        let text = main_text(page);
+        if text.contains("That's the right answer") {
+            Self::Correct
+        } else if text.contains("That's not the right answer") {
+            if text.contains("your answer is too high") {
+                Self::TooHigh
+            } else if text.contains("your answer is too low") {
+                Self::TooLow
+            } else {
+                Self::Incorrect
+            }
+        } else if text.contains("You gave an answer too recently") {
+            let wait = text
+                .split_once("You have ")
+                .and_then(|(_, rest)| rest.split_once(" left to wait"))
+                .map_or("a while", |(wait, _)| wait);
+            Self::RateLimited(wait.to_string())
+        } else if text.contains("You don't seem to be solving the right level") {
+            Self::WrongLevel
+        } else {
+            Self::Unknown(text)
+        }
+    }
+
+    /// Whether the verdict is final, so it is worth remembering.
+    fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Self::Correct | Self::Incorrect | Self::TooHigh | Self::TooLow
+        )
+    }
+}
+
+/// Text of the `<main>` element of a page with the tags stripped and whitespace collapsed.
+fn main_text(page: &str) -> String {
+    let main = page.split_once("<main>").map_or(page, |(_, rest)| {
+        rest.split("</main>").next().unwrap_or(rest)
+    });
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in main.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Correct => write!(f, "correct"),
+            Self::Incorrect => write!(f, "incorrect"),
+            Self::TooHigh => write!(f, "too high"),
+            Self::TooLow => write!(f, "too low"),
+            Self::RateLimited(wait) => write!(f, "rate limited, {wait} left to wait"),
+            Self::WrongLevel => write!(f, "already solved or not unlocked yet"),
+            Self::Unknown(text) => write!(f, "unexpected reply: {text}"),
+        }
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = anyhow::Error;
+
+    /// Parse one of the final verdicts as written in the log.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Self::Correct),
+            "incorrect" => Ok(Self::Incorrect),
+            "too high" => Ok(Self::TooHigh),
+            "too low" => Ok(Self::TooLow),
+            _ => bail!("unknown verdict `{s}`"),
+        }
+    }
+}
+
+/// Answers submitted for one day and what the site said about them.
+///
+/// Stored as one `part N: answer (verdict)` line per submission:
+///
+/// ```text
+/// part 1: 1900 (too high)
+/// part 1: 1896 (correct)
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Log(Vec<(Part, String, Verdict)>);
+
+impl Log {
+    /// Load the log at `path`, a missing file is an empty log.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(s) => s
+                .parse()
+                .with_context(|| format!("invalid submissions file {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn push(&mut self, part: Part, answer: &str, verdict: Verdict) {
+        self.0.push((part, answer.to_string(), verdict));
+    }
+
+    /// Why `answer` should not be submitted for `part`, if what was submitted before already
+    /// tells how it would be judged.
+    pub fn known(&self, part: Part, answer: &str) -> Option<String> {
+        let value = answer.parse::<i64>().ok();
+        for (_, submitted, verdict) in self.0.iter().filter(|(p, ..)| *p == part) {
+            if *verdict == Verdict::Correct {
+                return Some(format!("{part} is already solved with {submitted}"));
+            }
+            if submitted == answer {
+                return Some(format!("{answer} was already submitted, it is {verdict}"));
+            }
+            let bound = submitted.parse::<i64>().ok();
+            match (value, bound, verdict) {
+                (Some(v), Some(b), Verdict::TooHigh) if v >= b => {
+                    return Some(format!("{answer} is too high, {submitted} already was"));
+                }
+                (Some(v), Some(b), Verdict::TooLow) if v <= b => {
+                    return Some(format!("{answer} is too low, {submitted} already was"));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for Log {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut log = Self::default();
+        for (idx, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = || -> Option<(Part, &str, &str)> {
+                let (part, rest) = line.strip_prefix("part ")?.split_once(':')?;
+                let (answer, verdict) = rest.trim().rsplit_once(" (")?;
+                Some((part.parse().ok()?, answer, verdict.strip_suffix(')')?))
+            };
+            let Some((part, answer, verdict)) = entry() else {
+                bail!(
+                    "line {}: expected `part N: answer (verdict)`, found `{line}`",
+                    idx + 1
+                );
+            };
+            let verdict = verdict
+                .parse()
+                .with_context(|| format!("line {}", idx + 1))?;
+            log.push(part, answer, verdict);
+        }
+        Ok(log)
+    }
+}
+
+impl fmt::Display for Log {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (part, answer, verdict) in &self.0 {
+            writeln!(f, "{part}: {answer} ({verdict})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Submissions log of `day` below `root`.
+pub fn log_path(root: &Path, day: u32) -> PathBuf {
+    root.join(input::day_dir(day)).join(SUBMISSIONS_FILE)
+}
+
+/// Submit `answer` for one part of `day`, unless the log below `root` already tells how it
+/// would be judged. Final verdicts are added to the log, and a correct answer to the answers
+/// file next to it.
+///
+/// `client` is only built when the answer is sent, so a known answer needs no session token.
+pub fn submit(
+    client: impl FnOnce() -> Result<Client>,
+    root: &Path,
+    day: u32,
+    part: Part,
+    answer: &Answer,
+) -> Result<Verdict> {
+    if let Answer::Grid(_) = answer {
+        bail!("{answer}\nread the letters of the drawing and submit them by hand");
+    }
+    let answer = answer.to_string();
+    let path = log_path(root, day);
+    let mut log = Log::load(&path)?;
+    if let Some(reason) = log.known(part, &answer) {
+        bail!("not submitting: {reason}");
+    }
+    let level = part.number().to_string();
+    let (status, page) = client()?.post_form(
+        day,
+        "/answer",
+        &[("level", level.as_str()), ("answer", answer.as_str())],
+    )?;
+    if status != 200 {
+        bail!("unexpected status {status} submitting day {day} {part}");
+    }
+    let verdict = Verdict::parse(&page);
+    if verdict.is_final() {
+        log.push(part, &answer, verdict.clone());
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        log.save(&path)?;
+    }
+    if verdict == Verdict::Correct {
+        let path = path.with_file_name(ANSWERS_FILE);
+        let mut expected = Expected::load(&path)?;
+        expected.set(part, &answer);
+        expected.save(&path)?;
+    }
+    Ok(verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stub;
+
+    fn page(text: &str) -> String {
+        format!("<html><body><main>\n<article><p>{text}</p></article>\n</main></body></html>")
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        let parse = |text: &str| Verdict::parse(&page(text));
+        assert_eq!(
+            parse("That's the right answer! You are <em>one gold star</em> closer."),
+            Verdict::Correct
+        );
+        assert_eq!(
+            parse("That's not the right answer; your answer is too high.  If you're stuck..."),
+            Verdict::TooHigh
+        );
+        assert_eq!(
+            parse("That's not the right answer; your answer is too low."),
+            Verdict::TooLow
+        );
+        assert_eq!(
+            parse("That's not the right answer.  If you're stuck, make sure..."),
+            Verdict::Incorrect
+        );
+        assert_eq!(
+            parse(
+                "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 39s left to wait."
+            ),
+            Verdict::RateLimited("39s".to_string())
+        );
+        assert_eq!(
+            parse("You don't seem to be solving the right level.  Did you already complete it?"),
+            Verdict::WrongLevel
+        );
+        assert_eq!(parse("Hmm"), Verdict::Unknown("Hmm".to_string()));
+    }
+
+    #[test]
+    fn test_log() {
+        let text = "part 1: 1900 (too high)\npart 1: 10 (too low)\npart 2: abc (incorrect)\n";
+        let log: Log = text.parse().unwrap();
+        assert_eq!(log.to_string(), text);
+        assert!(log.known(Part::One, "1900").is_some());
+        assert!(log.known(Part::One, "2000").is_some());
+        assert!(log.known(Part::One, "5").is_some());
+        assert_eq!(log.known(Part::One, "1896"), None);
+        assert!(log.known(Part::Two, "abc").is_some());
+        assert_eq!(log.known(Part::Two, "1900"), None);
+
+        let mut solved = log.clone();
+        solved.push(Part::One, "1896", Verdict::Correct);
+        assert!(solved.known(Part::One, "1897").is_some());
+        assert!("part 1: 12".parse::<Log>().is_err());
+    }
+
+    #[test]
+    fn test_submit() {
+        let (url, server) = stub::serve(vec![
+            (
+                200,
+                "<main><p>That's not the right answer; your answer is too high.</p></main>",
+            ),
+            (
+                200,
+                "<main><p>You gave an answer too recently. You have 5s left to wait.</p></main>",
+            ),
+            (200, "<main><p>That's the right answer!</p></main>"),
+        ]);
+        let root = stub::temp_root("submit");
+        let client = || Ok(Client::new(&url, "abc", &root).with_min_interval(Default::default()));
+        let no_client = || bail!("no session token");
+        let answer = Answer::Integer(1900);
+
+        assert_eq!(
+            submit(client, &root, 16, Part::One, &answer).unwrap(),
+            Verdict::TooHigh
+        );
+        // known to be wrong, neither the stub nor a token is needed
+        let error = submit(no_client, &root, 16, Part::One, &answer).unwrap_err();
+        assert!(error.to_string().starts_with("not submitting"));
+        assert!(submit(no_client, &root, 16, Part::One, &Answer::Integer(2000)).is_err());
+        assert_eq!(
+            submit(client, &root, 16, Part::One, &Answer::Integer(1896)).unwrap(),
+            Verdict::RateLimited("5s".to_string())
+        );
+        let log = fs::read_to_string(log_path(&root, 16)).unwrap();
+        assert_eq!(log, "part 1: 1900 (too high)\n");
+        assert_eq!(
+            submit(client, &root, 16, Part::One, &Answer::Integer(1896)).unwrap(),
+            Verdict::Correct
+        );
+        let answers = Expected::load(&log_path(&root, 16).with_file_name(ANSWERS_FILE)).unwrap();
+        assert_eq!(answers.get(Part::One), Some("1896"));
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /2022/day/16/answer "));
+        assert!(requests[0].ends_with("level=1&answer=1900"));
+        assert!(requests[1].ends_with("level=1&answer=1896"));
+        assert!(requests[2].ends_with("level=1&answer=1896"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    Part, Puzzle,
+    input::{self, Source},
+    runner::{self, Outcome},
+};
+
+/// Name of the expected answers file, kept next to the input it belongs to.
+pub const ANSWERS_FILE: &str = "answers";
+
+/// Expected answers file for the input of `day` read from `source`.
+pub fn answers_path(day: u32, source: &Source) -> Result<PathBuf> {
+    match source {
+        Source::Default => Ok(input::day_dir(day).join(ANSWERS_FILE)),
+        Source::Path(path) => Ok(path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(ANSWERS_FILE)),
+        Source::Stdin => bail!("answers of an input read from stdin can not be verified"),
+    }
+}
+
+/// Known good answers of one input.
+///
+/// Stored as one `part N: answer` line per part, a multi-line answer starts on the line after its
+/// `part N:` header:
+///
+/// ```text
+/// part 1: 13140
+/// part 2:
+/// ##..##..
+/// ###...##
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Expected(BTreeMap<Part, String>);
+
+impl Expected {
+    /// Load expectations from `path`, a missing file has no expectations.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(s) => s
+                .parse()
+                .with_context(|| format!("invalid answers file {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, part: Part) -> Option<&str> {
+        self.0.get(&part).map(|s| s.as_str())
+    }
+
+    pub fn set(&mut self, part: Part, answer: &str) {
+        self.0.insert(part, normalize(answer).to_string());
+    }
+}
+
+/// Trailing whitespace is not significant, e.g. the final newline of a drawing.
+fn normalize(answer: &str) -> &str {
+    answer.trim_end()
+}
+
+fn parse_header(line: &str) -> Option<(Part, &str)> {
+    let rest = line.strip_prefix("part ")?;
+    let (part, answer) = rest.split_once(':')?;
+    Some((part.trim().parse().ok()?, answer.trim()))
+}
+
+impl FromStr for Expected {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut expected = Self::default();
+        let mut current: Option<(Part, Vec<&str>)> = None;
+        for (idx, line) in s.lines().enumerate() {
+            if let Some((part, answer)) = parse_header(line) {
+                if let Some((part, lines)) = current.take() {
+                    expected.set(part, &lines.join("\n"));
+                }
+                if answer.is_empty() {
+                    current = Some((part, vec![]));
+                } else {
+                    expected.set(part, answer);
+                }
+            } else if let Some((_, lines)) = current.as_mut() {
+                lines.push(line);
+            } else if !line.trim().is_empty() {
+                bail!(
+                    "line {}: expected `part N: answer`, found `{line}`",
+                    idx + 1
+                );
+            }
+        }
+        if let Some((part, lines)) = current {
+            expected.set(part, &lines.join("\n"));
+        }
+        Ok(expected)
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (part, answer) in &self.0 {
+            if answer.contains('\n') {
+                writeln!(f, "{part}:\n{answer}")?;
+            } else {
+                writeln!(f, "{part}: {answer}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// Holds the expected answer.
+    Fail(String),
+    /// No answer was recorded for this part yet.
+    Missing,
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub outcome: Outcome,
+    pub status: Status,
+}
+
+fn check(outcome: Outcome, expected: &Expected) -> Check {
+    let status = match (expected.get(outcome.part), &outcome.answer) {
+        (None, _) => Status::Missing,
+        (Some(e), Ok(answer)) if e == normalize(&answer.to_string()) => Status::Pass,
+        (Some(e), _) => Status::Fail(e.to_string()),
+    };
+    Check { outcome, status }
+}
+
+/// Run the given parts of a day and compare them with its answers file.
+pub fn verify(puzzle: &dyn Puzzle, source: &Source, parts: &[Part]) -> Result<Vec<Check>> {
+    let expected = Expected::load(&answers_path(puzzle.day(), source)?)?;
+    let outcomes = runner::quietly(|| runner::run_day(puzzle, source, parts));
+    Ok(outcomes.into_iter().map(|o| check(o, &expected)).collect())
+}
+
+/// Run the given parts of a day and store the answers in its answers file.
+///
+/// Parts that fail keep whatever was recorded before.
+pub fn record(puzzle: &dyn Puzzle, source: &Source, parts: &[Part]) -> Result<Vec<Outcome>> {
+    let path = answers_path(puzzle.day(), source)?;
+    let mut expected = Expected::load(&path)?;
+    let outcomes = runner::quietly(|| runner::run_day(puzzle, source, parts));
+    for outcome in &outcomes {
+        if let Ok(answer) = &outcome.answer {
+            expected.set(outcome.part, &answer.to_string());
+        }
+    }
+    expected.save(&path)?;
+    Ok(outcomes)
+}
+
+fn one_line(answer: &str) -> String {
+    if answer.contains('\n') {
+        format!("<{} lines>", answer.trim_end().lines().count())
+    } else {
+        answer.to_string()
+    }
+}
+
+/// Print one row per check, followed by a summary line.
+pub fn print_report(checks: &[Check]) {
+    println!("{:>3}  {:>4}  {:<7}  answer", "day", "part", "status");
+    for c in checks {
+        let answer = match &c.outcome.answer {
+            Ok(a) => one_line(&a.to_string()),
+            Err(e) => format!("FAILED: {e}"),
+        };
+        let (status, note) = match &c.status {
+            Status::Pass => ("pass", String::new()),
+            Status::Fail(e) => ("FAIL", format!(" (expected {})", one_line(e))),
+            Status::Missing => ("missing", String::new()),
+        };
+        println!(
+            "{:>3}  {:>4}  {status:<7}  {answer}{note}",
+            c.outcome.day,
+            c.outcome.part.number()
+        );
+    }
+    let count = |s: &Status| checks.iter().filter(|c| &c.status == s).count();
+    println!(
+        "{} passed, {} failed, {} missing",
+        count(&Status::Pass),
+        checks
+            .iter()
+            .filter(|c| matches!(c.status, Status::Fail(_)))
+            .count(),
+        count(&Status::Missing)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Answer;
+
+    static ANSWERS: &str = "part 1: 13140
+part 2:
+##..##..
+###...##
+";
+
+    #[test]
+    fn test_parse() {
+        let expected: Expected = ANSWERS.parse().unwrap();
+        assert_eq!(expected.get(Part::One), Some("13140"));
+        assert_eq!(expected.get(Part::Two), Some("##..##..\n###...##"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let expected: Expected = ANSWERS.parse().unwrap();
+        assert_eq!(expected.to_string(), ANSWERS);
+    }
+
+    #[test]
+    fn test_check() {
+        let expected: Expected = ANSWERS.parse().unwrap();
+        let outcome = |part, answer: &str| Outcome {
+            day: 10,
+            part,
+            answer: Ok(Answer::Text(answer.to_string())),
+            elapsed: Default::default(),
+            cpu: None,
+            alloc: None,
+        };
+        assert_eq!(
+            check(outcome(Part::One, "13140"), &expected).status,
+            Status::Pass
+        );
+        assert_eq!(
+            check(outcome(Part::Two, "##..##..\n###...##\n"), &expected).status,
+            Status::Pass
+        );
+        assert_eq!(
+            check(outcome(Part::One, "1"), &Expected::default()).status,
+            Status::Missing
+        );
+        assert_eq!(
+            check(outcome(Part::One, "1"), &expected).status,
+            Status::Fail("13140".to_string())
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    Answer, Part, Puzzle,
+    input::{self, Source},
+    runner::{self, Outcome, format_duration},
+};
+
+/// How often watched files are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Modification time and size of every watched file.
+type Snapshot = BTreeMap<PathBuf, (SystemTime, u64)>;
+
+/// Files to watch for the input of `day` read from `source`: everything in the day's input
+/// folder by default, or the given input file.
+fn watched(day: u32, source: &Source) -> Result<Vec<PathBuf>> {
+    match source {
+        Source::Default => {
+            let dir = input::day_dir(day);
+            match fs::read_dir(&dir) {
+                Ok(entries) => entries
+                    .map(|e| Ok(e?.path()))
+                    .collect::<Result<_, std::io::Error>>()
+                    .with_context(|| format!("failed to list {}", dir.display())),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+                Err(e) => Err(e).with_context(|| format!("failed to list {}", dir.display())),
+            }
+        }
+        Source::Path(path) => Ok(vec![path.clone()]),
+        Source::Stdin => bail!("an input read from stdin can not be watched"),
+    }
+}
+
+fn snapshot(paths: &[PathBuf]) -> Snapshot {
+    paths
+        .iter()
+        .filter_map(|p| {
+            let meta = fs::metadata(p).ok()?;
+            Some((p.clone(), (meta.modified().ok()?, meta.len())))
+        })
+        .collect()
+}
+
+/// Paths that were added, removed or modified between two snapshots.
+fn changed(prev: &Snapshot, next: &Snapshot) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = next
+        .iter()
+        .filter(|(p, v)| prev.get(*p) != Some(v))
+        .map(|(p, _)| p.clone())
+        .collect();
+    paths.extend(prev.keys().filter(|p| !next.contains_key(*p)).cloned());
+    paths.sort();
+    paths
+}
+
+fn describe(result: &Result<Answer, String>) -> String {
+    match result {
+        Ok(a) => a.to_string(),
+        Err(e) => format!("FAILED: {e}"),
+    }
+}
+
+/// Describe how the answer of a part changed since the previous run.
+///
+/// Multi-line answers that changed are shown line by line, `-` for the old and `+` for the new
+/// line.
+fn diff(prev: Option<&Outcome>, next: &Outcome) -> String {
+    let header = format!(
+        "day {:02} {} ({})",
+        next.day,
+        next.part,
+        format_duration(next.elapsed)
+    );
+    let new = describe(&next.answer);
+    let Some(prev) = prev else {
+        return if new.contains('\n') {
+            format!("{header}:\n{new}")
+        } else {
+            format!("{header}: {new}")
+        };
+    };
+    let old = describe(&prev.answer);
+    if old == new {
+        if new.contains('\n') {
+            format!("{header}: unchanged")
+        } else {
+            format!("{header}: {new} (unchanged)")
+        }
+    } else if old.contains('\n') || new.contains('\n') {
+        let mut out = format!("{header}: changed");
+        let (old, new): (Vec<_>, Vec<_>) = (old.lines().collect(), new.lines().collect());
+        for i in 0..old.len().max(new.len()) {
+            match (old.get(i), new.get(i)) {
+                (Some(o), Some(n)) if o == n => out.push_str(&format!("\n  {n}")),
+                (o, n) => {
+                    if let Some(o) = o {
+                        out.push_str(&format!("\n- {o}"));
+                    }
+                    if let Some(n) = n {
+                        out.push_str(&format!("\n+ {n}"));
+                    }
+                }
+            }
+        }
+        out
+    } else {
+        format!("{header}: {old} -> {new}")
+    }
+}
+
+fn run_and_report(
+    puzzle: &dyn Puzzle,
+    source: &Source,
+    parts: &[Part],
+    prev: &[Outcome],
+) -> Vec<Outcome> {
+    let outcomes = runner::quietly(|| runner::run_day(puzzle, source, parts));
+    for outcome in &outcomes {
+        let before = prev.iter().find(|o| o.part == outcome.part);
+        println!("{}", diff(before, outcome));
+    }
+    outcomes
+}
+
+/// Run the given parts of a day, then again every time one of its input files changes, printing
+/// how the answers differ from the previous run.
+///
+/// Only returns on error, stop it with Ctrl-C.
+pub fn watch(puzzle: &dyn Puzzle, source: &Source, parts: &[Part]) -> Result<()> {
+    let day = puzzle.day();
+    let mut files = watched(day, source)?;
+    let mut seen = snapshot(&files);
+    let mut outcomes = run_and_report(puzzle, source, parts, &[]);
+    println!("watching {} for changes", watched_description(day, source));
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        files = match source {
+            // pick up files created after the watch started
+            Source::Default => watched(day, source)?,
+            _ => files,
+        };
+        let now = snapshot(&files);
+        let paths = changed(&seen, &now);
+        if paths.is_empty() {
+            continue;
+        }
+        let names: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+        println!("\nchanged: {}", names.join(", "));
+        outcomes = run_and_report(puzzle, source, parts, &outcomes);
+        seen = now;
+    }
+}
+
+fn watched_description(day: u32, source: &Source) -> String {
+    match source {
+        Source::Path(path) => path.display().to_string(),
+        _ => format!("{}/*", input::day_dir(day).display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(answer: Result<Answer, String>) -> Outcome {
+        Outcome {
+            day: 10,
+            part: Part::One,
+            answer,
+            elapsed: Duration::from_micros(1500),
+            cpu: None,
+            alloc: None,
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = outcome(Ok(Answer::Integer(13140)));
+        let b = outcome(Ok(Answer::Integer(13141)));
+        assert_eq!(diff(None, &a), "day 10 part 1 (1.50ms): 13140");
+        assert_eq!(
+            diff(Some(&a), &a),
+            "day 10 part 1 (1.50ms): 13140 (unchanged)"
+        );
+        assert_eq!(diff(Some(&a), &b), "day 10 part 1 (1.50ms): 13140 -> 13141");
+        assert_eq!(
+            diff(Some(&b), &outcome(Err("oops".to_string()))),
+            "day 10 part 1 (1.50ms): 13141 -> FAILED: oops"
+        );
+    }
+
+    #[test]
+    fn test_diff_grid() {
+        let a = outcome(Ok(Answer::Grid("##..\n#..#".to_string())));
+        let b = outcome(Ok(Answer::Grid("##..\n#.##".to_string())));
+        assert_eq!(diff(None, &a), "day 10 part 1 (1.50ms):\n##..\n#..#");
+        assert_eq!(diff(Some(&a), &a), "day 10 part 1 (1.50ms): unchanged");
+        assert_eq!(
+            diff(Some(&a), &b),
+            "day 10 part 1 (1.50ms): changed\n  ##..\n- #..#\n+ #.##"
+        );
+    }
+
+    #[test]
+    fn test_changed() {
+        let t = SystemTime::UNIX_EPOCH;
+        let later = t + Duration::from_secs(1);
+        let prev: Snapshot = [("a".into(), (t, 1)), ("b".into(), (t, 1))].into();
+        let next: Snapshot = [("a".into(), (later, 1)), ("c".into(), (t, 1))].into();
+        let paths: Vec<PathBuf> = vec!["a".into(), "b".into(), "c".into()];
+        assert_eq!(changed(&prev, &next), paths);
+        assert!(changed(&prev, &prev).is_empty());
+    }
+}